@@ -3,7 +3,10 @@ pub mod logos;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use colored::{Color, Colorize};
+use crate::display::layout;
+use crate::display::palette::{self, Depth};
+use crate::display::theme::Style;
+use colored::Color;
 
 pub struct AsciiArt {
     pub lines: Vec<&'static str>,
@@ -43,14 +46,20 @@ impl AsciiArt {
         render_with_palette(line, &self.colors, use_colors, fallback)
     }
 
+    /// The text of a line in runs of one color, the same colors `render_line` uses.
+    pub fn spans(&self, index: usize, fallback: Color) -> Vec<(Color, String)> {
+        match self.lines.get(index) {
+            Some(line) => palette_spans(line, &self.colors, fallback),
+            None => Vec::new(),
+        }
+    }
+
     pub fn line_visible_width(&self, index: usize) -> usize {
         if index >= self.lines.len() {
             return 0;
         }
 
-        strip_color_tokens(self.lines[index])
-            .chars()
-            .count()
+        layout::width(&strip_color_tokens(self.lines[index]))
     }
 }
 
@@ -94,7 +103,7 @@ fn load_from_ascii_dir(id: &str, small: bool) -> Option<AsciiArt> {
         for raw in content.lines() {
             let raw_line = raw.trim_end_matches('\r').to_string();
             let cleaned = strip_color_tokens(&raw_line);
-            width = width.max(cleaned.chars().count());
+            width = width.max(layout::width(&cleaned));
             let leaked: &'static str = Box::leak(raw_line.into_boxed_str());
             lines.push(leaked);
         }
@@ -159,49 +168,38 @@ pub(crate) fn strip_color_tokens(line: &str) -> String {
     output
 }
 
+/// Logo colors for a distro: brand colors where the terminal shows them,
+/// the named colors of the terminal theme on 16-color terminals.
 pub fn palette_for_distro(id: &str) -> Vec<Color> {
-    match id {
-        "gentoo" => vec![
-            Color::Magenta,
-            Color::White,
-            Color::Magenta,
-            Color::White,
-            Color::Magenta,
-            Color::White,
-        ],
-        "macos" | "macosx" | "osx" | "darwin" => vec![
-            Color::Yellow,
-            Color::White,
-            Color::Yellow,
-            Color::White,
-            Color::Yellow,
-            Color::White,
-        ],
-        "arch" | "archlinux" => vec![
-            Color::Cyan,
-            Color::Blue,
-            Color::Cyan,
-            Color::Blue,
-            Color::Cyan,
-            Color::Blue,
-        ],
-        "debian" => vec![Color::Red, Color::White, Color::Red, Color::White, Color::Red, Color::White],
-        "ubuntu" => vec![Color::Red, Color::White, Color::Red, Color::White, Color::Red, Color::White],
-        "fedora" => vec![Color::Blue, Color::White, Color::Blue, Color::White, Color::Blue, Color::White],
-        "nixos" => vec![Color::Cyan, Color::Blue, Color::Cyan, Color::Blue, Color::Cyan, Color::Blue],
-        "alpine" => vec![Color::Blue, Color::White, Color::Blue, Color::White, Color::Blue, Color::White],
-        "manjaro" => vec![Color::Green, Color::White, Color::Green, Color::White, Color::Green, Color::White],
-        "endeavouros" => vec![Color::Magenta, Color::Cyan, Color::Magenta, Color::Cyan, Color::Magenta, Color::Cyan],
-        "pop" | "pop_os" | "pop!_os" => vec![Color::Cyan, Color::White, Color::Cyan, Color::White, Color::Cyan, Color::White],
-        "mint" | "linuxmint" => vec![Color::Green, Color::White, Color::Green, Color::White, Color::Green, Color::White],
-        "elementary" | "elementaryos" => vec![Color::White, Color::Cyan, Color::White, Color::Cyan, Color::White, Color::Cyan],
-        "zorin" | "zorinos" => vec![Color::Blue, Color::White, Color::Blue, Color::White, Color::Blue, Color::White],
-        "kali" => vec![Color::Blue, Color::White, Color::Blue, Color::White, Color::Blue, Color::White],
-        "parrot" | "parrotos" => vec![Color::Green, Color::Cyan, Color::Green, Color::Cyan, Color::Green, Color::Cyan],
-        "slackware" => vec![Color::Blue, Color::White, Color::Blue, Color::White, Color::Blue, Color::White],
-        "void" | "voidlinux" => vec![Color::Green, Color::White, Color::Green, Color::White, Color::Green, Color::White],
-        _ => vec![Color::Cyan, Color::White, Color::Cyan, Color::White, Color::Cyan, Color::White],
-    }
+    // (brand, named) for $1 and $2, repeated for $3..$6
+    let colors: [(&str, &str); 2] = match id {
+        "gentoo" => [("magenta", "magenta"), ("white", "white")],
+        "macos" | "macosx" | "osx" | "darwin" => [("yellow", "yellow"), ("white", "white")],
+        "arch" | "archlinux" => [("#1793d1", "cyan"), ("blue", "blue")],
+        "debian" => [("#d70a53", "red"), ("white", "white")],
+        "ubuntu" => [("#e95420", "red"), ("white", "white")],
+        "fedora" => [("#51a2da", "blue"), ("white", "white")],
+        "nixos" => [("#7ebae4", "cyan"), ("#5277c3", "blue")],
+        "alpine" => [("#0d597f", "blue"), ("white", "white")],
+        "manjaro" => [("#35bf5c", "green"), ("white", "white")],
+        "endeavouros" => [("magenta", "magenta"), ("cyan", "cyan")],
+        "pop" | "pop_os" | "pop!_os" => [("#48b9c7", "cyan"), ("white", "white")],
+        "mint" | "linuxmint" => [("#87cf3e", "green"), ("white", "white")],
+        "elementary" | "elementaryos" => [("white", "white"), ("cyan", "cyan")],
+        "zorin" | "zorinos" => [("blue", "blue"), ("white", "white")],
+        "kali" => [("blue", "blue"), ("white", "white")],
+        "parrot" | "parrotos" => [("green", "green"), ("cyan", "cyan")],
+        "slackware" => [("blue", "blue"), ("white", "white")],
+        "void" | "voidlinux" => [("#478061", "green"), ("white", "white")],
+        _ => [("cyan", "cyan"), ("white", "white")],
+    };
+    let named = palette::depth() == Depth::Ansi16;
+    colors
+        .iter()
+        .cycle()
+        .take(6)
+        .filter_map(|(brand, name)| palette::parse(if named { name } else { brand }))
+        .collect()
 }
 
 fn render_with_palette(line: &str, palette: &[Color], use_colors: bool, fallback: Color) -> String {
@@ -209,7 +207,15 @@ fn render_with_palette(line: &str, palette: &[Color], use_colors: bool, fallback
         return strip_color_tokens(line);
     }
 
-    let mut out = String::new();
+    palette_spans(line, palette, fallback)
+        .into_iter()
+        .map(|(color, text)| palette::paint(&text, &Style::fg(color)))
+        .collect()
+}
+
+// Splits a logo line at its `$1`..`$9` and `$R` tokens into runs of one color
+fn palette_spans(line: &str, palette: &[Color], fallback: Color) -> Vec<(Color, String)> {
+    let mut spans = Vec::new();
     let mut current = palette.first().copied().unwrap_or(fallback);
     let mut buffer = String::new();
     let mut chars = line.chars().peekable();
@@ -221,8 +227,7 @@ fn render_with_palette(line: &str, palette: &[Color], use_colors: bool, fallback
                     let idx = next.to_digit(10).unwrap_or(1) as usize;
                     chars.next();
                     if !buffer.is_empty() {
-                        out.push_str(&buffer.color(current).to_string());
-                        buffer.clear();
+                        spans.push((current, std::mem::take(&mut buffer)));
                     }
                     if idx == 0 {
                         current = fallback;
@@ -234,8 +239,7 @@ fn render_with_palette(line: &str, palette: &[Color], use_colors: bool, fallback
                 if next == 'R' || next == 'r' {
                     chars.next();
                     if !buffer.is_empty() {
-                        out.push_str(&buffer.color(current).to_string());
-                        buffer.clear();
+                        spans.push((current, std::mem::take(&mut buffer)));
                     }
                     current = fallback;
                     continue;
@@ -247,8 +251,8 @@ fn render_with_palette(line: &str, palette: &[Color], use_colors: bool, fallback
     }
 
     if !buffer.is_empty() {
-        out.push_str(&buffer.color(current).to_string());
+        spans.push((current, buffer));
     }
 
-    out
+    spans
 }
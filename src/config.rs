@@ -1,9 +1,13 @@
+use crate::display::modules::default_modules;
+use crate::display::LogoPosition;
+use crate::display::palette;
 use colored::Color;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub display: DisplayOptions,
@@ -13,6 +17,24 @@ pub struct Config {
 
     #[serde(default)]
     pub info: InfoOptions,
+
+    #[serde(default)]
+    pub templates: HashMap<String, TemplateOptions>,
+
+    #[serde(default)]
+    pub commands: CommandOptions,
+
+    #[serde(default)]
+    pub cache: CacheOptions,
+
+    #[serde(default)]
+    pub history: HistoryOptions,
+
+    #[serde(default)]
+    pub theme: ThemeOptions,
+
+    #[serde(default)]
+    pub bars: BarOptions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +50,33 @@ pub struct DisplayOptions {
 
     #[serde(default)]
     pub ascii_distro: Option<String>,
+
+    #[serde(default = "default_modules")]
+    pub modules: Vec<String>,
+
+    // Left, right, top or bottom of the info
+    #[serde(default = "default_logo_position")]
+    pub logo_position: LogoPosition,
+
+    // Columns between the logo and the info beside it
+    #[serde(default = "default_logo_gap")]
+    pub logo_gap: usize,
+
+    // Where the info starts next to the logo, then moved down `info_offset`
+    // lines
+    #[serde(default = "default_info_align")]
+    pub info_align: InfoAlign,
+
+    #[serde(default)]
+    pub info_offset: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InfoAlign {
+    Top,
+    // Centered on the logo
+    Center,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,10 +86,15 @@ pub struct ColorOptions {
 
     #[serde(default = "default_white")]
     pub secondary: String,
+
+    // Replaces the distro colors of the logo, $1 is the first entry
+    #[serde(default)]
+    pub logo: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InfoOptions {
+    // System
     #[serde(default = "default_true")]
     pub os: bool,
     #[serde(default = "default_true")]
@@ -50,42 +104,289 @@ pub struct InfoOptions {
     #[serde(default = "default_true")]
     pub uptime: bool,
     #[serde(default = "default_true")]
-    pub packages: bool,
+    pub load_average: bool,
     #[serde(default = "default_true")]
-    pub shell: bool,
+    pub processes: bool,
     #[serde(default = "default_true")]
-    pub resolution: bool,
+    pub logged_users: bool,
+    #[serde(default = "default_true")]
+    pub machine_type: bool,
+    #[serde(default = "default_true")]
+    pub init_system: bool,
+    #[serde(default = "default_true")]
+    pub boot_time: bool,
+
+    // Hardware
+    #[serde(default = "default_true")]
+    pub cpu: bool,
+    #[serde(default = "default_true")]
+    pub cpu_arch: bool,
+    #[serde(default = "default_true")]
+    pub cpu_cache: bool,
+    #[serde(default = "default_true")]
+    pub cpu_temp: bool,
+    #[serde(default = "default_true")]
+    pub cpu_governor: bool,
+    #[serde(default = "default_true")]
+    pub gpu: bool,
+    #[serde(default = "default_true")]
+    pub memory: bool,
+    #[serde(default = "default_true")]
+    pub swap: bool,
+    #[serde(default = "default_true")]
+    pub disk: bool,
+    #[serde(default = "default_true")]
+    pub motherboard: bool,
+    #[serde(default = "default_true")]
+    pub bios: bool,
+
+    // Desktop
     #[serde(default = "default_true")]
     pub de: bool,
     #[serde(default = "default_true")]
     pub wm: bool,
     #[serde(default = "default_true")]
+    pub wm_theme: bool,
+    #[serde(default = "default_true")]
     pub theme: bool,
     #[serde(default = "default_true")]
     pub icons: bool,
     #[serde(default = "default_true")]
+    pub cursor: bool,
+    #[serde(default = "default_true")]
     pub terminal: bool,
     #[serde(default = "default_true")]
-    pub cpu: bool,
+    pub terminal_font: bool,
     #[serde(default = "default_true")]
-    pub gpu: bool,
+    pub shell: bool,
     #[serde(default = "default_true")]
-    pub memory: bool,
+    pub shell_version: bool,
     #[serde(default = "default_true")]
-    pub disk: bool,
+    pub display_server: bool,
+    #[serde(default = "default_true")]
+    pub resolution: bool,
+
+    // Network, power, audio and packages
     #[serde(default = "default_true")]
     pub network: bool,
     #[serde(default = "default_true")]
     pub battery: bool,
+    #[serde(default = "default_true")]
+    pub brightness: bool,
+    #[serde(default = "default_true")]
+    pub audio_device: bool,
+    #[serde(default = "default_true")]
+    pub volume: bool,
+    #[serde(default = "default_true")]
+    pub packages: bool,
+
+    // Misc
+    #[serde(default = "default_true")]
+    pub locale: bool,
+    #[serde(default = "default_true")]
+    pub timezone: bool,
+    #[serde(default = "default_true")]
+    pub virtualization: bool,
+    #[serde(default = "default_true")]
+    pub container: bool,
+    #[serde(default = "default_true")]
+    pub security: bool,
+    #[serde(default = "default_true")]
+    pub ssh_connection: bool,
+    #[serde(default = "default_true")]
+    pub bluetooth: bool,
     #[cfg(feature = "network")]
     #[serde(default)]
     pub public_ip: bool,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplateOptions {
+    #[serde(default)]
+    pub label: Option<String>,
+
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandOptions {
+    // Longest a single external command may run
+    #[serde(default = "default_command_timeout")]
+    pub timeout_ms: u64,
+
+    // Total time all commands of one fetch may take, 0 for no limit
+    #[serde(default = "default_command_budget")]
+    pub budget_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheOptions {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    #[serde(default)]
+    pub ttl: CacheTtl,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryOptions {
+    // Append a record to the history file on every fetch
+    #[serde(default)]
+    pub enabled: bool,
+
+    // Oldest records are dropped beyond this
+    #[serde(default = "default_history_records")]
+    pub max_records: usize,
+}
+
+// A built-in theme or theme file, with per-element overrides on top. Theme
+// files have the same layout without `name`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeOptions {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub label: StyleOptions,
+    #[serde(default)]
+    pub separator: StyleOptions,
+    #[serde(default)]
+    pub value: StyleOptions,
+    #[serde(default)]
+    pub title: StyleOptions,
+    #[serde(default)]
+    pub underline: StyleOptions,
+    #[serde(default)]
+    pub colors: ColorBarOptions,
+}
+
+// Unset fields come from the theme below. Colors take the same values as
+// [colors], plus "primary", "secondary" and "none".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StyleOptions {
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub background: Option<String>,
+    #[serde(default)]
+    pub bold: Option<bool>,
+    #[serde(default)]
+    pub italic: Option<bool>,
+    #[serde(default)]
+    pub dim: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ColorBarOptions {
+    #[serde(flatten)]
+    pub style: StyleOptions,
+    // Text of each block, blank blocks show the colors as background
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub palette: Option<Vec<String>>,
+}
+
+impl ThemeOptions {
+    /// `self` with the fields it leaves unset taken from `base`.
+    pub fn over(self, base: ThemeOptions) -> ThemeOptions {
+        ThemeOptions {
+            name: self.name.or(base.name),
+            label: self.label.over(base.label),
+            separator: self.separator.over(base.separator),
+            value: self.value.over(base.value),
+            title: self.title.over(base.title),
+            underline: self.underline.over(base.underline),
+            colors: ColorBarOptions {
+                style: self.colors.style.over(base.colors.style),
+                symbol: self.colors.symbol.or(base.colors.symbol),
+                palette: self.colors.palette.or(base.colors.palette),
+            },
+        }
+    }
+}
+
+impl StyleOptions {
+    fn over(self, base: StyleOptions) -> StyleOptions {
+        StyleOptions {
+            color: self.color.or(base.color),
+            background: self.background.or(base.background),
+            bold: self.bold.or(base.bold),
+            italic: self.italic.or(base.italic),
+            dim: self.dim.or(base.dim),
+        }
+    }
+}
+
+// Usage bars of memory, swap, disks, battery and brightness. `{bar}` works in
+// their templates in any mode, `mode` only changes the default templates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BarOptions {
+    #[serde(default = "default_bar_mode")]
+    pub mode: BarMode,
+    #[serde(default = "default_bar_width")]
+    pub width: usize,
+    #[serde(default = "default_bar_filled")]
+    pub filled: String,
+    #[serde(default = "default_bar_empty")]
+    pub empty: String,
+    #[serde(default = "default_bar_left")]
+    pub left: String,
+    #[serde(default = "default_bar_right")]
+    pub right: String,
+    // Percent at which bars turn yellow, then red. The battery counts the
+    // charge that is used up, so a low battery turns red.
+    #[serde(default = "default_bar_warning")]
+    pub warning: u8,
+    #[serde(default = "default_bar_critical")]
+    pub critical: u8,
+    // Colors below `warning`, below `critical` and above, empty for the
+    // color of the value
+    #[serde(default = "default_bar_colors")]
+    pub colors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BarMode {
+    Off,
+    // After the numbers
+    Beside,
+    // In place of the numbers
+    Instead,
+}
+
+// Seconds a cached value stays valid, 0 disables caching for the field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheTtl {
+    #[serde(default = "default_ttl_hour")]
+    pub packages: u64,
+    #[serde(default = "default_ttl_day")]
+    pub gpu: u64,
+    #[serde(default = "default_ttl_week")]
+    pub motherboard: u64,
+    #[serde(default = "default_ttl_week")]
+    pub bios: u64,
+    #[serde(default = "default_ttl_day")]
+    pub terminal_font: u64,
+}
+
 fn default_true() -> bool {
     true
 }
 
+fn default_logo_position() -> LogoPosition {
+    LogoPosition::Left
+}
+
+fn default_logo_gap() -> usize {
+    2
+}
+
+fn default_info_align() -> InfoAlign {
+    InfoAlign::Top
+}
+
 fn default_cyan() -> String {
     "auto".to_string()
 }
@@ -94,14 +395,64 @@ fn default_white() -> String {
     "white".to_string()
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            display: DisplayOptions::default(),
-            colors: ColorOptions::default(),
-            info: InfoOptions::default(),
-        }
-    }
+fn default_command_timeout() -> u64 {
+    2000
+}
+
+fn default_command_budget() -> u64 {
+    5000
+}
+
+fn default_history_records() -> usize {
+    5000
+}
+
+fn default_bar_mode() -> BarMode {
+    BarMode::Off
+}
+
+fn default_bar_width() -> usize {
+    10
+}
+
+fn default_bar_filled() -> String {
+    "■".to_string()
+}
+
+fn default_bar_empty() -> String {
+    "□".to_string()
+}
+
+fn default_bar_left() -> String {
+    "[".to_string()
+}
+
+fn default_bar_right() -> String {
+    "]".to_string()
+}
+
+fn default_bar_warning() -> u8 {
+    60
+}
+
+fn default_bar_critical() -> u8 {
+    85
+}
+
+fn default_bar_colors() -> Vec<String> {
+    ["green", "yellow", "red"].iter().map(|c| c.to_string()).collect()
+}
+
+fn default_ttl_hour() -> u64 {
+    60 * 60
+}
+
+fn default_ttl_day() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_ttl_week() -> u64 {
+    7 * 24 * 60 * 60
 }
 
 impl Default for DisplayOptions {
@@ -111,6 +462,11 @@ impl Default for DisplayOptions {
             show_colors: true,
             small_ascii: false,
             ascii_distro: None,
+            modules: default_modules(),
+            logo_position: default_logo_position(),
+            logo_gap: default_logo_gap(),
+            info_align: default_info_align(),
+            info_offset: 0,
         }
     }
 }
@@ -120,6 +476,62 @@ impl Default for ColorOptions {
         Self {
             primary: "auto".to_string(),
             secondary: "white".to_string(),
+            logo: Vec::new(),
+        }
+    }
+}
+
+impl Default for CommandOptions {
+    fn default() -> Self {
+        Self {
+            timeout_ms: default_command_timeout(),
+            budget_ms: default_command_budget(),
+        }
+    }
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl: CacheTtl::default(),
+        }
+    }
+}
+
+impl Default for HistoryOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_records: default_history_records(),
+        }
+    }
+}
+
+impl Default for BarOptions {
+    fn default() -> Self {
+        Self {
+            mode: default_bar_mode(),
+            width: default_bar_width(),
+            filled: default_bar_filled(),
+            empty: default_bar_empty(),
+            left: default_bar_left(),
+            right: default_bar_right(),
+            warning: default_bar_warning(),
+            critical: default_bar_critical(),
+            colors: default_bar_colors(),
+        }
+    }
+}
+
+impl Default for CacheTtl {
+    fn default() -> Self {
+        Self {
+            packages: default_ttl_hour(),
+            gpu: default_ttl_day(),
+            motherboard: default_ttl_week(),
+            bios: default_ttl_week(),
+            terminal_font: default_ttl_day(),
         }
     }
 }
@@ -131,38 +543,180 @@ impl Default for InfoOptions {
             kernel: true,
             hostname: true,
             uptime: true,
-            packages: true,
-            shell: true,
-            resolution: true,
+            load_average: true,
+            processes: true,
+            logged_users: true,
+            machine_type: true,
+            init_system: true,
+            boot_time: true,
+            cpu: true,
+            cpu_arch: true,
+            cpu_cache: true,
+            cpu_temp: true,
+            cpu_governor: true,
+            gpu: true,
+            memory: true,
+            swap: true,
+            disk: true,
+            motherboard: true,
+            bios: true,
             de: true,
             wm: true,
+            wm_theme: true,
             theme: true,
             icons: true,
+            cursor: true,
             terminal: true,
-            cpu: true,
-            gpu: true,
-            memory: true,
-            disk: true,
+            terminal_font: true,
+            shell: true,
+            shell_version: true,
+            display_server: true,
+            resolution: true,
             network: true,
             battery: true,
+            brightness: true,
+            audio_device: true,
+            volume: true,
+            packages: true,
+            locale: true,
+            timezone: true,
+            virtualization: true,
+            container: true,
+            security: true,
+            ssh_connection: true,
+            bluetooth: true,
             #[cfg(feature = "network")]
             public_ip: false,
         }
     }
 }
 
-impl Config {
-    pub fn load() -> Self {
-        if let Some(path) = Self::config_path() {
-            if path.exists() {
-                if let Ok(content) = fs::read_to_string(&path) {
-                    if let Ok(config) = toml::from_str(&content) {
-                        return config;
-                    }
-                }
+impl InfoOptions {
+    /// Options with only the named toggles enabled. Fails on a name that is
+    /// not a toggle.
+    pub fn only(toggles: &[&str]) -> Result<Self, String> {
+        let mut options = Self::none();
+        for toggle in toggles {
+            match *toggle {
+                "os" => options.os = true,
+                "kernel" => options.kernel = true,
+                "hostname" => options.hostname = true,
+                "uptime" => options.uptime = true,
+                "load_average" => options.load_average = true,
+                "processes" => options.processes = true,
+                "logged_users" => options.logged_users = true,
+                "machine_type" => options.machine_type = true,
+                "init_system" => options.init_system = true,
+                "boot_time" => options.boot_time = true,
+                "cpu" => options.cpu = true,
+                "cpu_arch" => options.cpu_arch = true,
+                "cpu_cache" => options.cpu_cache = true,
+                "cpu_temp" => options.cpu_temp = true,
+                "cpu_governor" => options.cpu_governor = true,
+                "gpu" => options.gpu = true,
+                "memory" => options.memory = true,
+                "swap" => options.swap = true,
+                "disk" => options.disk = true,
+                "motherboard" => options.motherboard = true,
+                "bios" => options.bios = true,
+                "de" => options.de = true,
+                "wm" => options.wm = true,
+                "wm_theme" => options.wm_theme = true,
+                "theme" => options.theme = true,
+                "icons" => options.icons = true,
+                "cursor" => options.cursor = true,
+                "terminal" => options.terminal = true,
+                "terminal_font" => options.terminal_font = true,
+                "shell" => options.shell = true,
+                "shell_version" => options.shell_version = true,
+                "display_server" => options.display_server = true,
+                "resolution" => options.resolution = true,
+                "network" => options.network = true,
+                "battery" => options.battery = true,
+                "brightness" => options.brightness = true,
+                "audio_device" => options.audio_device = true,
+                "volume" => options.volume = true,
+                "packages" => options.packages = true,
+                "locale" => options.locale = true,
+                "timezone" => options.timezone = true,
+                "virtualization" => options.virtualization = true,
+                "container" => options.container = true,
+                "security" => options.security = true,
+                "ssh_connection" => options.ssh_connection = true,
+                "bluetooth" => options.bluetooth = true,
+                #[cfg(feature = "network")]
+                "public_ip" => options.public_ip = true,
+                // Never gathered without the feature
+                #[cfg(not(feature = "network"))]
+                "public_ip" => {}
+                name => return Err(format!("unknown [info] toggle '{}'", name)),
             }
         }
-        Self::default()
+        Ok(options)
+    }
+
+    fn none() -> Self {
+        Self {
+            os: false,
+            kernel: false,
+            hostname: false,
+            uptime: false,
+            load_average: false,
+            processes: false,
+            logged_users: false,
+            machine_type: false,
+            init_system: false,
+            boot_time: false,
+            cpu: false,
+            cpu_arch: false,
+            cpu_cache: false,
+            cpu_temp: false,
+            cpu_governor: false,
+            gpu: false,
+            memory: false,
+            swap: false,
+            disk: false,
+            motherboard: false,
+            bios: false,
+            de: false,
+            wm: false,
+            wm_theme: false,
+            theme: false,
+            icons: false,
+            cursor: false,
+            terminal: false,
+            terminal_font: false,
+            shell: false,
+            shell_version: false,
+            display_server: false,
+            resolution: false,
+            network: false,
+            battery: false,
+            brightness: false,
+            audio_device: false,
+            volume: false,
+            packages: false,
+            locale: false,
+            timezone: false,
+            virtualization: false,
+            container: false,
+            security: false,
+            ssh_connection: false,
+            bluetooth: false,
+            #[cfg(feature = "network")]
+            public_ip: false,
+        }
+    }
+}
+
+impl Config {
+    /// The config file in the user's config dir, the defaults when there is
+    /// none.
+    pub fn load() -> Result<Self, String> {
+        match Self::config_path() {
+            Some(path) if path.exists() => Self::load_from(&path.to_string_lossy()),
+            _ => Ok(Self::default()),
+        }
     }
 
     pub fn load_from(path: &str) -> Result<Self, String> {
@@ -175,28 +729,47 @@ impl Config {
     }
 
     pub fn primary_color(&self) -> Color {
-        parse_color(&self.colors.primary)
-    }
-}
-
-fn parse_color(name: &str) -> Color {
-    match name.to_lowercase().as_str() {
-        "black" => Color::Black,
-        "red" => Color::Red,
-        "green" => Color::Green,
-        "yellow" => Color::Yellow,
-        "blue" => Color::Blue,
-        "magenta" | "purple" => Color::Magenta,
-        "cyan" => Color::Cyan,
-        "white" => Color::White,
-        "bright_black" | "brightblack" => Color::BrightBlack,
-        "bright_red" | "brightred" => Color::BrightRed,
-        "bright_green" | "brightgreen" => Color::BrightGreen,
-        "bright_yellow" | "brightyellow" => Color::BrightYellow,
-        "bright_blue" | "brightblue" => Color::BrightBlue,
-        "bright_magenta" | "brightmagenta" => Color::BrightMagenta,
-        "bright_cyan" | "brightcyan" => Color::BrightCyan,
-        "bright_white" | "brightwhite" => Color::BrightWhite,
-        _ => Color::Cyan,
+        palette::parse(&self.colors.primary).unwrap_or(Color::Cyan)
+    }
+
+    /// None for "auto" and "distro", which follow the logo.
+    pub fn secondary_color(&self) -> Option<Color> {
+        palette::parse(&self.colors.secondary)
+    }
+
+    /// The configured logo colors, None to keep the distro palette.
+    pub fn logo_palette(&self) -> Option<Vec<Color>> {
+        let colors: Vec<Color> = self.colors.logo.iter().filter_map(|c| palette::parse(c)).collect();
+        (!colors.is_empty()).then_some(colors)
+    }
+
+    /// Problems worth telling the user about, like colors that do not parse.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let primary = self.colors.primary.to_lowercase();
+        if primary != "auto" && primary != "distro" && palette::parse(&primary).is_none() {
+            warnings.push(format!("unknown color '{}' for colors.primary, using cyan", self.colors.primary));
+        }
+        let secondary = self.colors.secondary.to_lowercase();
+        if secondary != "auto" && secondary != "distro" && palette::parse(&secondary).is_none() {
+            warnings.push(format!("unknown color '{}' for colors.secondary", self.colors.secondary));
+        }
+        if self.bars.warning > self.bars.critical {
+            warnings.push(format!(
+                "bars.warning ({}) is above bars.critical ({}), bars skip yellow",
+                self.bars.warning, self.bars.critical
+            ));
+        }
+        for color in &self.bars.colors {
+            if palette::parse(color).is_none() {
+                warnings.push(format!("unknown color '{}' in bars.colors, using the value color", color));
+            }
+        }
+        for color in &self.colors.logo {
+            if palette::parse(color).is_none() {
+                warnings.push(format!("unknown color '{}' in colors.logo, skipped", color));
+            }
+        }
+        warnings
     }
 }
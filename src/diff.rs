@@ -0,0 +1,289 @@
+// Field-by-field comparison of two `SystemInfo`s for `hyperfetch diff`.
+//
+// Both sides are compared in their JSON form. Objects are walked field by
+// field, lists of objects are matched by the element's mount, name or
+// manager (by position when elements have none), and other lists are
+// compared as a whole. Paths use the `--get` syntax, so any reported path
+// can be queried directly.
+//
+// Readings that change on every run (uptime, load, usage, temperatures)
+// are left out unless all fields are requested.
+
+use crate::display::format;
+use crate::info::SystemInfo;
+use crate::query::ELEMENT_KEYS;
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+// Matched against the field's path with every selector left empty, so
+// `disks[].percent` is the use of any disk and a nested field that happens to
+// share a name is still compared
+const VOLATILE: &[&str] = &[
+    "uptime_seconds",
+    "load_average",
+    "processes",
+    "logged_users",
+    "boot_time",
+    "cpu_freq_mhz",
+    "cpu_temp_celsius",
+    "memory_used",
+    "swap_used",
+    "brightness_percent",
+    "volume_percent",
+    "volume_muted",
+    "battery",
+    // Summary of package_counts, which is compared per manager
+    "packages",
+    "disks[].used_bytes",
+    "disks[].available_bytes",
+    "disks[].percent",
+    "gpu[].temp_celsius",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Change {
+    pub path: String,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Value>,
+    // new - old for numbers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta: Option<Value>,
+}
+
+/// Changes from `old` to `new`. `all` includes the volatile readings.
+pub fn diff(old: &SystemInfo, new: &SystemInfo, all: bool) -> Vec<Change> {
+    let old = serde_json::to_value(old).unwrap_or_default();
+    let new = serde_json::to_value(new).unwrap_or_default();
+
+    let mut changes = Vec::new();
+    if let (Value::Object(old), Value::Object(new)) = (&old, &new) {
+        compare_maps(&mut changes, "", "", old, new, all);
+    }
+    changes
+}
+
+// `field` is `prefix` without the selectors, for matching against VOLATILE
+fn compare_maps(
+    changes: &mut Vec<Change>,
+    prefix: &str,
+    field: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    all: bool,
+) {
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        let child = join(field, key);
+        if !all && VOLATILE.contains(&child.as_str()) {
+            continue;
+        }
+        compare(changes, join(prefix, key), &child, old.get(key), new.get(key), all);
+    }
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+fn compare(changes: &mut Vec<Change>, path: String, field: &str, old: Option<&Value>, new: Option<&Value>, all: bool) {
+    // A null field was not gathered, the same as a missing one
+    let old = old.filter(|v| !v.is_null());
+    let new = new.filter(|v| !v.is_null());
+
+    // Empty lists are not serialized, a missing list has no elements
+    let no_elements = Value::Array(Vec::new());
+    let is_list = |value: Option<&Value>| value.is_some_and(|v| matches!(v, Value::Array(items) if is_object_list(items)));
+    let (old, new) = match (old, new) {
+        (None, Some(_)) if is_list(new) => (Some(&no_elements), new),
+        (Some(_), None) if is_list(old) => (old, Some(&no_elements)),
+        sides => sides,
+    };
+
+    match (old, new) {
+        (None, None) => {}
+        (None, Some(new)) => changes.push(Change {
+            path,
+            kind: ChangeKind::Added,
+            old: None,
+            new: Some(without_volatile(new, field, all)),
+            delta: None,
+        }),
+        (Some(old), None) => changes.push(Change {
+            path,
+            kind: ChangeKind::Removed,
+            old: Some(without_volatile(old, field, all)),
+            new: None,
+            delta: None,
+        }),
+        (Some(Value::Object(old)), Some(Value::Object(new))) => compare_maps(changes, &path, field, old, new, all),
+        (Some(Value::Array(old)), Some(Value::Array(new)))
+            if (is_object_list(old) || old.is_empty()) && (is_object_list(new) || new.is_empty()) =>
+        {
+            compare_lists(changes, &path, field, old, new, all)
+        }
+        (Some(old), Some(new)) if old != new => changes.push(Change {
+            path,
+            kind: ChangeKind::Changed,
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+            delta: delta(old, new),
+        }),
+        _ => {}
+    }
+}
+
+// Added and removed elements are shown without the fields left out of the comparison
+fn without_volatile(value: &Value, field: &str, all: bool) -> Value {
+    match value {
+        Value::Object(map) if !all => Value::Object(
+            map.iter()
+                .filter_map(|(key, value)| {
+                    let child = join(field, key);
+                    (!VOLATILE.contains(&child.as_str())).then(|| (key.clone(), without_volatile(value, &child, all)))
+                })
+                .collect(),
+        ),
+        Value::Array(items) if !all => {
+            let child = format!("{}[]", field);
+            Value::Array(items.iter().map(|item| without_volatile(item, &child, all)).collect())
+        }
+        _ => value.clone(),
+    }
+}
+
+fn is_object_list(items: &[Value]) -> bool {
+    !items.is_empty() && items.iter().all(Value::is_object)
+}
+
+fn compare_lists(changes: &mut Vec<Change>, path: &str, field: &str, old: &[Value], new: &[Value], all: bool) {
+    let field = format!("{}[]", field);
+    let old_keys = element_keys(old);
+    let new_keys = element_keys(new);
+
+    // Keys unique on both sides, otherwise elements can only be matched by position
+    let keyed = |keys: &[String]| {
+        let mut sorted = keys.to_vec();
+        sorted.sort();
+        sorted.dedup();
+        sorted.len() == keys.len()
+    };
+    if !(keyed(&old_keys) && keyed(&new_keys)) {
+        for i in 0..old.len().max(new.len()) {
+            compare(changes, format!("{}[{}]", path, i), &field, old.get(i), new.get(i), all);
+        }
+        return;
+    }
+
+    // Old order first, then elements that only exist in the new list
+    let mut keys = old_keys.clone();
+    keys.extend(new_keys.iter().filter(|k| !old_keys.contains(k)).cloned());
+    for key in keys {
+        let old = old_keys.iter().position(|k| *k == key).map(|i| &old[i]);
+        let new = new_keys.iter().position(|k| *k == key).map(|i| &new[i]);
+        compare(changes, format!("{}[{}]", path, key), &field, old, new, all);
+    }
+}
+
+// The selector for each element, its position when it has no identifying field
+fn element_keys(items: &[Value]) -> Vec<String> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            ELEMENT_KEYS
+                .iter()
+                .find_map(|key| item.get(key).and_then(Value::as_str))
+                .map(str::to_string)
+                .unwrap_or_else(|| i.to_string())
+        })
+        .collect()
+}
+
+fn delta(old: &Value, new: &Value) -> Option<Value> {
+    if let (Some(old), Some(new)) = (old.as_u64(), new.as_u64()) {
+        return i64::try_from(new as i128 - old as i128).ok().map(Value::from);
+    }
+    if let (Some(old), Some(new)) = (old.as_i64(), new.as_i64()) {
+        return new.checked_sub(old).map(Value::from);
+    }
+    let (old, new) = (old.as_f64()?, new.as_f64()?);
+    serde_json::Number::from_f64(new - old).map(Value::Number)
+}
+
+pub fn print_text(changes: &[Change]) {
+    if changes.is_empty() {
+        println!("No changes");
+        return;
+    }
+
+    for change in changes {
+        let bytes = change.path.ends_with("_bytes") || change.path.ends_with("_total") || change.path.ends_with("_used");
+        let show = |value: &Option<Value>| value.as_ref().map(|v| display_value(v, bytes)).unwrap_or_default();
+
+        match change.kind {
+            ChangeKind::Added => {
+                println!("{} {}: {}", "+".green().bold(), change.path.green(), show(&change.new));
+            }
+            ChangeKind::Removed => {
+                println!("{} {}: {}", "-".red().bold(), change.path.red(), show(&change.old));
+            }
+            ChangeKind::Changed => {
+                let delta = match &change.delta {
+                    Some(delta) => format!(" ({})", signed(delta, bytes)),
+                    None => String::new(),
+                };
+                println!(
+                    "{} {}: {} → {}{}",
+                    "~".yellow().bold(),
+                    change.path.yellow(),
+                    show(&change.old),
+                    show(&change.new),
+                    delta
+                );
+            }
+        }
+    }
+}
+
+fn display_value(value: &Value, bytes: bool) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) if bytes => n.as_u64().map(format::bytes).unwrap_or_else(|| n.to_string()),
+        Value::Object(_) | Value::Array(_) => serde_json::to_string(value).unwrap_or_default(),
+        _ => value.to_string(),
+    }
+}
+
+// "+5", "−3", "+1.50 GiB"
+fn signed(delta: &Value, bytes: bool) -> String {
+    let negative = delta.as_f64().is_some_and(|d| d < 0.0);
+    let magnitude = match delta.as_i64() {
+        Some(d) if bytes => format::bytes(d.unsigned_abs()),
+        Some(d) => d.unsigned_abs().to_string(),
+        None => {
+            let d = delta.as_f64().unwrap_or_default().abs();
+            // Shortest form, the same as JSON
+            serde_json::Number::from_f64(d).map(|n| n.to_string()).unwrap_or_default()
+        }
+    };
+    format::signed(negative, magnitude)
+}
@@ -0,0 +1,57 @@
+// Usage bars like `[■■■■■□□□□□]` for the modules with a percentage.
+//
+// The bar is the `{bar}` placeholder of memory, swap, disk, battery and
+// brightness. Its color comes from the level it shows: green, then yellow
+// past `warning`, then red past `critical`.
+
+use crate::config::BarOptions;
+use crate::display::palette;
+use colored::Color;
+
+/// What a full bar means, which decides its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    // Full is bad: memory, swap, disks
+    Usage,
+    // Empty is bad: the battery
+    Charge,
+    // Neither, shown in the color of the value: brightness
+    Level,
+}
+
+/// A bar as it appears in the text of a value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bar {
+    pub text: String,
+    pub color: Option<Color>,
+}
+
+impl Bar {
+    pub fn new(percent: f64, scale: Scale, options: &BarOptions) -> Self {
+        let percent = percent.clamp(0.0, 100.0);
+        let filled = ((percent / 100.0 * options.width as f64).round() as usize).min(options.width);
+        let text = format!(
+            "{}{}{}{}",
+            options.left,
+            options.filled.repeat(filled),
+            options.empty.repeat(options.width - filled),
+            options.right
+        );
+
+        let used = match scale {
+            Scale::Usage => percent,
+            Scale::Charge => 100.0 - percent,
+            Scale::Level => return Self { text, color: None },
+        };
+        let level = if used >= options.critical as f64 {
+            2
+        } else if used >= options.warning as f64 {
+            1
+        } else {
+            0
+        };
+        let color = options.colors.get(level).and_then(|c| palette::parse(c));
+
+        Self { text, color }
+    }
+}
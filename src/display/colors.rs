@@ -1,11 +1,41 @@
 use crate::ascii::AsciiArt;
+use crate::config::{BarOptions, TemplateOptions};
+use crate::display::bar::Bar;
+use crate::display::layout;
+use crate::display::modules::{collect_lines, default_modules, InfoLine};
 use crate::info::SystemInfo;
-use colored::{Color, Colorize};
+use crate::display::palette;
+use crate::display::theme::{Style, Theme};
+use clap::ValueEnum;
+use colored::Color;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Where the logo goes relative to the info.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogoPosition {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
 
 pub struct DisplayConfig {
     pub show_ascii: bool,
     pub use_colors: bool,
-    pub primary_color: Color,
+    pub theme: Theme,
+    pub modules: Vec<String>,
+    pub templates: HashMap<String, TemplateOptions>,
+    pub bars: BarOptions,
+    // Columns to fit the output in, None for no limit
+    pub width: Option<usize>,
+    pub logo_position: LogoPosition,
+    pub logo_gap: usize,
+    // Lines the info starts below the top of the logo, after centering
+    pub info_offset: usize,
+    pub center_info: bool,
 }
 
 impl Default for DisplayConfig {
@@ -13,287 +43,319 @@ impl Default for DisplayConfig {
         Self {
             show_ascii: true,
             use_colors: true,
-            primary_color: Color::Cyan,
+            theme: Theme::default(),
+            modules: default_modules(),
+            templates: HashMap::new(),
+            bars: BarOptions::default(),
+            width: None,
+            logo_position: LogoPosition::Left,
+            logo_gap: 2,
+            info_offset: 0,
+            center_info: false,
         }
     }
 }
 
-pub fn print_info(info: &SystemInfo, ascii: &AsciiArt, config: &DisplayConfig) {
-    let mut info_lines = Vec::new();
-
-    // System
-    push_opt(&mut info_lines, "OS", info.os.as_deref(), config);
-    push_opt(&mut info_lines, "Kernel", info.kernel.as_deref(), config);
-    push_opt(&mut info_lines, "Host", info.hostname.as_deref(), config);
-    push_opt(&mut info_lines, "Uptime", info.uptime.as_deref(), config);
-    push_opt(&mut info_lines, "Machine", info.machine_type.as_deref(), config);
-    push_opt(&mut info_lines, "Init", info.init_system.as_deref(), config);
-    push_opt(&mut info_lines, "Packages", info.packages.as_deref(), config);
-
-    if let Some(shell) = info.shell.as_ref() {
-        let shell_str = info
-            .shell_version
-            .as_ref()
-            .map(|ver| format!("{shell} {ver}"))
-            .unwrap_or_else(|| shell.clone());
-        info_lines.push(format_line("Shell", &shell_str, config));
-    }
+/// A run of text in one style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
 
-    push_opt(&mut info_lines, "Display", info.display_server.as_deref(), config);
-    push_opt(&mut info_lines, "Resolution", info.resolution.as_deref(), config);
-    push_opt(&mut info_lines, "DE", info.de.as_deref(), config);
-    push_opt(&mut info_lines, "WM", info.wm.as_deref(), config);
-    push_opt(&mut info_lines, "Theme", info.theme.as_deref(), config);
-    push_opt(&mut info_lines, "Icons", info.icons.as_deref(), config);
-    push_opt(&mut info_lines, "Cursor", info.cursor.as_deref(), config);
-
-    if let Some(terminal) = info.terminal.as_ref() {
-        let term_str = info
-            .terminal_font
-            .as_ref()
-            .map(|font| format!("{terminal} ({font})"))
-            .unwrap_or_else(|| terminal.clone());
-        info_lines.push(format_line("Terminal", &term_str, config));
+impl Span {
+    fn plain(text: impl Into<String>) -> Self {
+        Self::styled(text, Style::default())
     }
 
-    // Hardware
-    push_sep(&mut info_lines);
-    if let Some(cpu) = info.cpu.as_ref() {
-        let threads = info
-            .cpu_threads
-            .map(|t| t.to_string())
-            .unwrap_or_else(|| "?".to_string());
-        let freq = info.cpu_freq.as_deref().unwrap_or("?");
-        let cpu_str = format!("{cpu} ({threads}) @ {freq}");
-        info_lines.push(format_line("CPU", &cpu_str, config));
-    }
-    push_opt(&mut info_lines, "Arch", info.cpu_arch.as_deref(), config);
-    push_opt(&mut info_lines, "Cache", info.cpu_cache.as_deref(), config);
-    push_opt(&mut info_lines, "CPU Temp", info.cpu_temp.as_deref(), config);
-    push_opt(&mut info_lines, "Governor", info.cpu_governor.as_deref(), config);
-
-    for gpu in &info.gpu {
-        let mut gpu_str = gpu.name.clone();
-        if let Some(ref driver) = gpu.driver {
-            gpu_str.push_str(&format!(" [{}]", driver));
-        }
-        if let Some(ref vram) = gpu.vram {
-            gpu_str.push_str(&format!(" ({})", vram));
-        }
-        if let Some(ref temp) = gpu.temp {
-            gpu_str.push_str(&format!(" @ {}", temp));
+    fn styled(text: impl Into<String>, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
         }
-        info_lines.push(format_line("GPU", &gpu_str, config));
-    }
-
-    push_opt(&mut info_lines, "Memory", info.memory.as_deref(), config);
-    push_opt(&mut info_lines, "Swap", info.swap.as_deref(), config);
-    push_opt(&mut info_lines, "Load", info.load_average.as_deref(), config);
-    if let Some(procs) = info.processes {
-        info_lines.push(format_line("Processes", &procs.to_string(), config));
     }
 
-    // Disks
-    for disk in &info.disks {
-        let disk_str = format!(
-            "{} / {} ({}%) [{}{}]",
-            disk.used,
-            disk.size,
-            disk.percent,
-            disk.filesystem,
-            disk.disk_type
-                .as_ref()
-                .map(|t| format!(", {}", t))
-                .unwrap_or_default()
-        );
-        info_lines.push(format_line(&format!("Disk ({})", disk.mount), &disk_str, config));
+    fn render(&self, use_colors: bool) -> String {
+        if !use_colors {
+            return self.text.clone();
+        }
+        palette::paint(&self.text, &self.style)
     }
+}
 
-    // Board info
-    push_opt(&mut info_lines, "Board", info.motherboard.as_deref(), config);
-    push_opt(&mut info_lines, "BIOS", info.bios.as_deref(), config);
+pub fn print_info(info: &SystemInfo, ascii: &AsciiArt, config: &DisplayConfig) {
+    print_lines(render_info(info, ascii, config));
+}
 
-    // Network
-    push_sep(&mut info_lines);
-    for iface in &info.interfaces {
-        let mut parts = Vec::new();
-        if let Some(ref ip) = iface.ipv4 {
-            parts.push(ip.clone());
-        }
-        if let Some(ref ip) = iface.ipv6 {
-            parts.push(ip.clone());
-        }
-        if let Some(ref speed) = iface.speed {
-            parts.push(speed.clone());
-        }
-        if let Some(ref state) = iface.state {
-            if state != "up" {
-                parts.push(format!("[{}]", state));
+// Stops quietly when the reader went away, as with `hyperfetch | head -3`
+fn print_lines(lines: impl IntoIterator<Item = String>) {
+    let mut out = io::stdout().lock();
+    for line in lines {
+        if let Err(e) = writeln!(out, "{}", line) {
+            if e.kind() != io::ErrorKind::BrokenPipe {
+                eprintln!("Error writing output: {}", e);
             }
-        }
-        if !parts.is_empty() {
-            info_lines.push(format_line(&format!("Net ({})", iface.name), &parts.join(", "), config));
+            return;
         }
     }
+}
 
-    #[cfg(feature = "network")]
-    if let Some(public_ip) = info.public_ip.as_ref() {
-        let mut ip_str = public_ip.ip.clone();
-        let mut location_parts = Vec::new();
-        if let Some(city) = public_ip.city.as_ref() {
-            location_parts.push(city.clone());
-        }
-        if let Some(region) = public_ip.region.as_ref() {
-            location_parts.push(region.clone());
-        }
-        if let Some(country) = public_ip.country.as_ref() {
-            location_parts.push(country.clone());
-        }
-        if let Some(zip) = public_ip.zip.as_ref() {
-            location_parts.push(zip.clone());
-        }
-        if !location_parts.is_empty() {
-            ip_str.push_str(&format!(" ({})", location_parts.join(", ")));
+/// The output lines of a fetch: logo and info side by side, or only the info.
+pub fn render_info(info: &SystemInfo, ascii: &AsciiArt, config: &DisplayConfig) -> Vec<String> {
+    styled_info(info, ascii, config)
+        .iter()
+        .map(|line| line.iter().map(|span| span.render(config.use_colors)).collect())
+        .collect()
+}
+
+/// The lines of `render_info` as styled spans, for output that is not a terminal.
+pub fn styled_info(info: &SystemInfo, ascii: &AsciiArt, config: &DisplayConfig) -> Vec<Vec<Span>> {
+    let lines = collect_lines(info, &config.modules, &config.templates, &config.bars);
+    let info_lines = render_lines(&lines, config);
+
+    let beside = matches!(config.logo_position, LogoPosition::Left | LogoPosition::Right);
+    let lines = match config.width {
+        _ if !config.show_ascii => info_lines,
+        _ if !beside => stack_with_ascii(ascii, info_lines, config),
+        Some(width) if is_too_narrow(ascii, &info_lines, width, config) => {
+            stack_with_ascii(ascii, info_lines, config)
         }
-        if let Some(isp) = public_ip.isp.as_ref() {
-            ip_str.push_str(&format!(" [{}]", isp));
+        // The info gets what the logo leaves
+        Some(width) => {
+            let info_width = width.saturating_sub(ascii.width + config.logo_gap);
+            let info_lines = info_lines.into_iter().map(|line| ellipsize(line, info_width)).collect();
+            combine_with_ascii(ascii, info_lines, config)
         }
-        info_lines.push(format_line("Public IP", &ip_str, config));
+        None => combine_with_ascii(ascii, info_lines, config),
+    };
+    match config.width {
+        Some(width) => lines.into_iter().map(|line| ellipsize(line, width)).collect(),
+        None => lines,
     }
+}
 
-    // Power
-    if let Some(battery) = info.battery.as_ref() {
-        let mut bat_str = format!("{}% ({})", battery.percent, battery.status);
-        if let Some(time) = battery.time_remaining.as_ref() {
-            bat_str.push_str(&format!(" ~{}", time));
-        }
-        info_lines.push(format_line("Battery", &bat_str, config));
+// Beside the logo the info would get less than this many columns, or less
+// than its longest line when that is shorter
+const MIN_INFO_WIDTH: usize = 40;
+
+fn is_too_narrow(ascii: &AsciiArt, info_lines: &[Vec<Span>], width: usize, config: &DisplayConfig) -> bool {
+    let info_width = info_lines.iter().map(|line| line_width(line)).max().unwrap_or(0);
+    width < ascii.width + config.logo_gap + info_width.min(MIN_INFO_WIDTH)
+}
+
+fn line_width(line: &[Span]) -> usize {
+    line.iter().map(|span| layout::width(&span.text)).sum()
+}
+
+// Cut to `width` columns, the last of them an ellipsis
+fn ellipsize(mut line: Vec<Span>, width: usize) -> Vec<Span> {
+    if line_width(&line) <= width {
+        return line;
     }
-    push_opt(&mut info_lines, "Brightness", info.brightness.as_deref(), config);
-
-    // Audio
-    push_opt(&mut info_lines, "Audio", info.audio_device.as_deref(), config);
-    push_opt(&mut info_lines, "Volume", info.volume.as_deref(), config);
-
-    // Misc
-    push_sep(&mut info_lines);
-    push_opt(&mut info_lines, "Locale", info.locale.as_deref(), config);
-    push_opt(&mut info_lines, "Timezone", info.timezone.as_deref(), config);
-    push_opt(&mut info_lines, "Boot Time", info.boot_time.as_deref(), config);
-    push_opt(&mut info_lines, "Users", info.logged_users.as_deref(), config);
-    push_opt(&mut info_lines, "Virt", info.virtualization.as_deref(), config);
-    push_opt(&mut info_lines, "Container", info.container.as_deref(), config);
-    push_opt(&mut info_lines, "Security", info.security.as_deref(), config);
-    push_opt(&mut info_lines, "SSH", info.ssh_connection.as_deref(), config);
-    push_opt(&mut info_lines, "Bluetooth", info.bluetooth.as_deref(), config);
-
-    // Add color bar
-    info_lines.push(String::new());
-    info_lines.push(color_bar());
-
-    // Print combined output
-    if config.show_ascii {
-        print_with_ascii(ascii, &info_lines, config);
-    } else {
-        for line in info_lines {
-            println!("{}", line);
+    // Padding after the logo on lines without info is not worth an ellipsis
+    while line
+        .last()
+        .is_some_and(|span| span.text.trim().is_empty() && span.style.background.is_none())
+    {
+        line.pop();
+    }
+    if line_width(&line) <= width {
+        return line;
+    }
+
+    let mut out = Vec::new();
+    let mut used = 0;
+    let budget = width.saturating_sub(1);
+    for span in line {
+        // Measured as a whole, emoji sequences are narrower than their parts
+        let mut text = String::new();
+        for c in span.text.chars() {
+            text.push(c);
+            if used + layout::width(&text) > budget {
+                text.pop();
+                break;
+            }
+        }
+        used += layout::width(&text);
+        let full = text.len() == span.text.len();
+        let style = span.style;
+        if !text.is_empty() {
+            out.push(Span::styled(text, style));
+        }
+        if !full {
+            if width > 0 {
+                out.push(Span::styled("…", style));
+            }
+            break;
         }
     }
+    out
 }
 
-fn format_line(label: &str, value: &str, config: &DisplayConfig) -> String {
-    if config.use_colors {
-        format!(
-            "{}{} {}",
-            label.color(config.primary_color).bold(),
-            ":".color(config.primary_color),
-            value
-        )
-    } else {
-        format!("{}: {}", label, value)
+fn render_lines(lines: &[InfoLine], config: &DisplayConfig) -> Vec<Vec<Span>> {
+    let title_width = lines
+        .iter()
+        .find_map(|line| match line {
+            InfoLine::Title { user, host } => Some(layout::width(user) + layout::width(host) + 1),
+            _ => None,
+        })
+        .unwrap_or(0);
+    let field_width = lines
+        .iter()
+        .map(|line| match line {
+            InfoLine::Field { label, value, .. } => layout::width(label) + layout::width(value) + 2,
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0);
+
+    let mut out = Vec::new();
+    for line in lines {
+        match line {
+            InfoLine::Field { label, value, bar } => out.push(format_line(label, value, bar.as_ref(), config)),
+            InfoLine::Title { user, host } => out.push(format_title(user, host, config)),
+            InfoLine::Underline => out.push(vec![Span::styled("-".repeat(title_width), config.theme.underline)]),
+            InfoLine::Rule => {
+                let width = field_width.max(title_width);
+                out.push(vec![Span::styled("-".repeat(width), config.theme.underline)]);
+            }
+            InfoLine::Blank => out.push(Vec::new()),
+            InfoLine::Colors => out.extend(color_bar(&config.theme)),
+        }
     }
+    out
 }
 
-fn push_opt(lines: &mut Vec<String>, label: &str, value: Option<&str>, config: &DisplayConfig) {
-    if let Some(value) = value {
-        lines.push(format_line(label, value, config));
+fn format_line(label: &str, value: &str, bar: Option<&Bar>, config: &DisplayConfig) -> Vec<Span> {
+    let theme = &config.theme;
+    let mut spans = vec![
+        Span::styled(label, theme.label),
+        Span::styled(":", theme.separator),
+        Span::plain(" "),
+    ];
+
+    // The bar in its level color, the text around it as a value
+    match bar.and_then(|bar| value.split_once(&bar.text).map(|parts| (bar, parts))) {
+        Some((bar, (before, after))) => {
+            let bar_style = Style {
+                color: bar.color.or(theme.value.color),
+                ..theme.value
+            };
+            spans.push(Span::styled(before, theme.value));
+            spans.push(Span::styled(bar.text.clone(), bar_style));
+            spans.push(Span::styled(after, theme.value));
+            spans.retain(|span| !span.text.is_empty());
+        }
+        None => spans.push(Span::styled(value, theme.value)),
     }
+    spans
 }
 
-fn push_sep(lines: &mut Vec<String>) {
-    lines.push(String::new());
+fn format_title(user: &str, host: &str, config: &DisplayConfig) -> Vec<Span> {
+    let theme = &config.theme;
+    vec![
+        Span::styled(user, theme.title),
+        Span::styled("@", theme.separator),
+        Span::styled(host, theme.title),
+    ]
 }
 
-fn print_with_ascii(ascii: &AsciiArt, info_lines: &[String], config: &DisplayConfig) {
-    let ascii_width = ascii.width + 2; // Add padding
+fn combine_with_ascii(ascii: &AsciiArt, info_lines: Vec<Vec<Span>>, config: &DisplayConfig) -> Vec<Vec<Span>> {
     let primary_color = ascii.colors.first().copied().unwrap_or(Color::White);
 
-    let max_lines = ascii.lines.len().max(info_lines.len());
+    // Rows of the info next to the logo
+    let mut top = config.info_offset;
+    if config.center_info {
+        top += ascii.lines.len().saturating_sub(info_lines.len()) / 2;
+    }
+    let max_lines = ascii.lines.len().max(top + info_lines.len());
+    let info_width = info_lines.iter().map(|line| line_width(line)).max().unwrap_or(0);
+    let mut info_lines = info_lines.into_iter();
 
+    let mut out = Vec::with_capacity(max_lines);
     for i in 0..max_lines {
-        // Print ASCII art line (or padding)
-        let ascii_line = if i < ascii.lines.len() {
-            let rendered = ascii.render_line(i, config.use_colors, primary_color);
-            let visible_len = ascii.line_visible_width(i);
-            let padding = if ascii_width > visible_len {
-                ascii_width - visible_len
-            } else {
-                0
-            };
-            format!("{}{}", rendered, " ".repeat(padding))
-        } else {
-            " ".repeat(ascii_width)
+        let logo = logo_line(ascii, i, primary_color);
+        let info = match i.checked_sub(top) {
+            Some(_) => info_lines.next().unwrap_or_default(),
+            None => Vec::new(),
         };
 
-        // Print info line
-        let info_line = if i < info_lines.len() {
-            &info_lines[i]
+        let mut line = Vec::new();
+        if config.logo_position == LogoPosition::Right {
+            // Info, padded to a column, then the logo
+            let padding = info_width + config.logo_gap - line_width(&info);
+            line.extend(info);
+            if !logo.is_empty() {
+                line.push(Span::plain(" ".repeat(padding)));
+                line.extend(logo);
+            }
         } else {
-            ""
-        };
-
-        println!("{}{}", ascii_line, info_line);
+            // ASCII art line (or padding), then the info
+            let padding = (ascii.width + config.logo_gap).saturating_sub(ascii.line_visible_width(i));
+            line.extend(logo);
+            line.push(Span::plain(" ".repeat(padding)));
+            line.extend(info);
+        }
+        out.push(line);
     }
+    out
 }
 
-fn color_bar() -> String {
-    let colors = [
-        Color::Black,
-        Color::Red,
-        Color::Green,
-        Color::Yellow,
-        Color::Blue,
-        Color::Magenta,
-        Color::Cyan,
-        Color::White,
-    ];
+// The logo, a blank line, then the info, or the other way round
+fn stack_with_ascii(ascii: &AsciiArt, info_lines: Vec<Vec<Span>>, config: &DisplayConfig) -> Vec<Vec<Span>> {
+    let primary_color = ascii.colors.first().copied().unwrap_or(Color::White);
+    let logo: Vec<Vec<Span>> = (0..ascii.lines.len())
+        .map(|i| logo_line(ascii, i, primary_color))
+        .collect();
 
-    let mut bar = String::new();
-    for color in &colors {
-        bar.push_str(&"   ".on_color(*color).to_string());
-    }
-    bar.push_str("\n");
-    for color in &colors {
-        bar.push_str(&"   ".on_color(*color).to_string());
-    }
+    let (first, second) = if config.logo_position == LogoPosition::Bottom {
+        (info_lines, logo)
+    } else {
+        (logo, info_lines)
+    };
+    let mut out = first;
+    out.push(Vec::new());
+    out.extend(second);
+    out
+}
 
-    bar
+fn logo_line(ascii: &AsciiArt, index: usize, fallback: Color) -> Vec<Span> {
+    ascii
+        .spans(index, fallback)
+        .into_iter()
+        .map(|(color, text)| Span::styled(text, Style::fg(color)))
+        .collect()
 }
 
-pub fn print_logo_only(ascii: &AsciiArt, config: &DisplayConfig) {
-    let primary_color = ascii.colors.first().copied().unwrap_or(Color::White);
+// Blank blocks show the palette as background, other symbols in its colors
+fn color_bar(theme: &Theme) -> [Vec<Span>; 2] {
+    let blank = theme.bar_symbol.trim().is_empty();
+    let bar: Vec<Span> = theme
+        .bar_palette
+        .iter()
+        .map(|color| {
+            let style = if blank {
+                Style {
+                    background: Some(*color),
+                    ..theme.color_bar
+                }
+            } else {
+                Style {
+                    color: Some(*color),
+                    ..theme.color_bar
+                }
+            };
+            Span::styled(theme.bar_symbol.clone(), style)
+        })
+        .collect();
 
-    for i in 0..ascii.lines.len() {
-        let rendered = ascii.render_line(i, config.use_colors, primary_color);
-        println!("{}", rendered);
-    }
+    [bar.clone(), bar]
 }
 
-pub fn print_json(info: &SystemInfo) {
-    match serde_json::to_string_pretty(info) {
-        Ok(json) => println!("{}", json),
-        Err(e) => eprintln!("Error serializing to JSON: {}", e),
-    }
+pub fn print_logo_only(ascii: &AsciiArt, config: &DisplayConfig) {
+    let primary_color = ascii.colors.first().copied().unwrap_or(Color::White);
+
+    print_lines((0..ascii.lines.len()).map(|i| ascii.render_line(i, config.use_colors, primary_color)));
 }
 
 pub fn list_logos() {
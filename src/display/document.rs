@@ -0,0 +1,154 @@
+// Markdown and HTML reports, for bug reports and wiki pages where terminal
+// output with ANSI codes does not paste.
+//
+// Both show the configured modules with the same labels and values as the
+// terminal, grouped under the `SECTIONS` headings. Layout entries (title,
+// separators, the color bar) have no place in a table and are left out.
+
+use crate::ascii::AsciiArt;
+use crate::display::colors::DisplayConfig;
+use crate::display::modules::{collect_lines, InfoLine, SECTIONS};
+use crate::info::SystemInfo;
+use colored::Color;
+use std::fmt::Write;
+
+type Section = (&'static str, Vec<(String, String)>);
+
+const STYLE: &str = "\
+body { margin: 0; padding: 2em; background: #1e1e1e; color: #cccccc; font-family: ui-monospace, \
+'DejaVu Sans Mono', Menlo, Consolas, monospace; }
+main { display: flex; flex-wrap: wrap; gap: 3em; align-items: flex-start; }
+pre.logo { margin: 0; line-height: 1.2; }
+h1 { font-size: 1.2em; margin: 0 0 0.5em; color: var(--primary); }
+h2 { font-size: 1em; margin: 1.2em 0 0.3em; color: var(--primary); }
+table { border-collapse: collapse; }
+th { text-align: left; padding: 0.1em 1.5em 0.1em 0; color: var(--primary); white-space: nowrap; }
+td { padding: 0.1em 0; }
+";
+
+pub fn markdown(info: &SystemInfo, config: &DisplayConfig) -> String {
+    let mut out = format!("# {}\n", escape_markdown(&title(info, config)));
+    for (name, rows) in sections(info, config) {
+        let _ = write!(out, "\n## {}\n\n| Field | Value |\n| --- | --- |\n", name);
+        for (label, value) in rows {
+            let _ = writeln!(out, "| {} | {} |", escape_markdown(&label), escape_markdown(&value));
+        }
+    }
+    out
+}
+
+/// A page without external resources: the logo in its palette colors next
+/// to the tables.
+pub fn html(info: &SystemInfo, ascii: &AsciiArt, config: &DisplayConfig) -> String {
+    let title = escape_html(&title(info, config));
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>{}</title>", title);
+    let primary = config.theme.label.color.map_or_else(|| "#cccccc".to_string(), css_color);
+    let _ = writeln!(out, "<style>\n:root {{ --primary: {}; }}\n{}</style>", primary, STYLE);
+    let _ = writeln!(out, "</head>\n<body>\n<main>");
+
+    if config.show_ascii {
+        let fallback = ascii.colors.first().copied().unwrap_or(Color::White);
+        out.push_str("<pre class=\"logo\">");
+        for index in 0..ascii.lines.len() {
+            for (color, text) in ascii.spans(index, fallback) {
+                let _ = write!(out, "<span style=\"color: {}\">{}</span>", css_color(color), escape_html(&text));
+            }
+            out.push('\n');
+        }
+        out.push_str("</pre>\n");
+    }
+
+    let _ = writeln!(out, "<div class=\"info\">\n<h1>{}</h1>", title);
+    for (name, rows) in sections(info, config) {
+        let _ = writeln!(out, "<h2>{}</h2>\n<table>", name);
+        for (label, value) in rows {
+            let _ = writeln!(out, "<tr><th>{}</th><td>{}</td></tr>", escape_html(&label), escape_html(&value));
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</div>\n</main>\n</body>\n</html>\n");
+    out
+}
+
+// Lines of each module in the configured order, under the section it belongs to
+fn sections(info: &SystemInfo, config: &DisplayConfig) -> Vec<Section> {
+    let mut sections: Vec<Section> = SECTIONS.iter().map(|(name, _)| (*name, Vec::new())).collect();
+    for module in &config.modules {
+        let module = module.trim();
+        let Some(index) = SECTIONS.iter().position(|(_, modules)| modules.contains(&module)) else {
+            continue;
+        };
+        for line in collect_lines(info, &[module.to_string()], &config.templates, &config.bars) {
+            if let InfoLine::Field { label, value, .. } = line {
+                sections[index].1.push((label, value));
+            }
+        }
+    }
+    sections.retain(|(_, rows)| !rows.is_empty());
+    sections
+}
+
+// user@host, as on the title line of the fetch
+fn title(info: &SystemInfo, config: &DisplayConfig) -> String {
+    match collect_lines(info, &["title".to_string()], &config.templates, &config.bars).pop() {
+        Some(InfoLine::Title { user, host }) => format!("{}@{}", user, host),
+        _ => "hyperfetch".to_string(),
+    }
+}
+
+/// CSS color for a terminal color, from a dark terminal theme.
+pub fn css_color(color: Color) -> String {
+    let hex = match color {
+        Color::Black => "#000000",
+        Color::Red => "#cd3131",
+        Color::Green => "#0dbc79",
+        Color::Yellow => "#e5e510",
+        Color::Blue => "#2472c8",
+        Color::Magenta => "#bc3fbc",
+        Color::Cyan => "#11a8cd",
+        Color::White => "#e5e5e5",
+        Color::BrightBlack => "#666666",
+        Color::BrightRed => "#f14c4c",
+        Color::BrightGreen => "#23d18b",
+        Color::BrightYellow => "#f5f543",
+        Color::BrightBlue => "#3b8eea",
+        Color::BrightMagenta => "#d670d6",
+        Color::BrightCyan => "#29b8db",
+        Color::BrightWhite => "#ffffff",
+        Color::TrueColor { r, g, b } => return format!("#{:02x}{:02x}{:02x}", r, g, b),
+    };
+    hex.to_string()
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Table cells are one line, and characters with meaning in Markdown are escaped
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' | '|' | '*' | '_' | '`' | '<' | '[' | ']' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            '\n' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,95 @@
+// Human-readable text for the raw numbers in `SystemInfo`.
+//
+// The info model carries bytes, seconds, MHz, °C and percentages. These
+// helpers turn them into the strings shown in the info lines and are what
+// the derived template placeholders (`{memory}`, `{uptime}`, ...) use.
+
+pub fn bytes(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+    const TB: u64 = GB * 1024;
+
+    if bytes >= TB {
+        format!("{:.2} TiB", bytes as f64 / TB as f64)
+    } else if bytes >= GB {
+        format!("{:.2} GiB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.2} MiB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.2} KiB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+/// A change with its sign, "+5" or "−1.50 GiB". `magnitude` is the absolute
+/// value, already formatted.
+pub fn signed(negative: bool, magnitude: impl std::fmt::Display) -> String {
+    let sign = if negative { "−" } else { "+" };
+    format!("{}{}", sign, magnitude)
+}
+
+/// "1.23 GiB / 15.50 GiB (8%)"
+pub fn usage(used: u64, total: u64) -> String {
+    const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+    let percent = if total > 0 {
+        used as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    };
+    format!("{:.2} GiB / {:.2} GiB ({:.0}%)", used as f64 / GIB, total as f64 / GIB, percent)
+}
+
+pub fn uptime(secs: u64) -> String {
+    let days = secs / 86400;
+    let hours = (secs % 86400) / 3600;
+    let mins = (secs % 3600) / 60;
+
+    let mut parts = Vec::new();
+    if days > 0 {
+        parts.push(format!("{} day{}", days, if days == 1 { "" } else { "s" }));
+    }
+    if hours > 0 {
+        parts.push(format!("{} hour{}", hours, if hours == 1 { "" } else { "s" }));
+    }
+    if mins > 0 || parts.is_empty() {
+        parts.push(format!("{} min{}", mins, if mins == 1 { "" } else { "s" }));
+    }
+
+    parts.join(", ")
+}
+
+pub fn frequency(mhz: f64) -> String {
+    if mhz >= 1000.0 {
+        format!("{:.2} GHz", mhz / 1000.0)
+    } else {
+        format!("{:.0} MHz", mhz)
+    }
+}
+
+// Whole degrees, sensors report millidegrees that are only noise here
+pub fn temperature(celsius: f64) -> String {
+    format!("{}°C", celsius as i64)
+}
+
+pub fn load(load: &[f64; 3]) -> String {
+    format!("{:.2}, {:.2}, {:.2}", load[0], load[1], load[2])
+}
+
+pub fn volume(percent: u32, muted: bool) -> String {
+    if muted {
+        format!("{}% (Muted)", percent)
+    } else {
+        format!("{}%", percent)
+    }
+}
+
+/// Battery time as "h:mm".
+pub fn duration_minutes(minutes: u32) -> String {
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+pub fn mebibytes(bytes: u64) -> String {
+    format!("{} MiB", bytes / 1024 / 1024)
+}
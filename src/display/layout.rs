@@ -0,0 +1,38 @@
+// Terminal size and the display width of text.
+//
+// Widths count terminal columns as given by the Unicode width tables: East
+// Asian wide characters and emoji take two, combining marks and zero-width
+// characters none, and emoji sequences joined by ZWJ count once.
+
+use unicode_width::UnicodeWidthStr;
+
+/// Columns `text` takes in a terminal.
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// Columns of the terminal on stdout, or `COLUMNS` when that is set. None
+/// when stdout is not a terminal, so piped output is never cut.
+pub fn terminal_width() -> Option<usize> {
+    let window = window_width()?;
+    let columns = std::env::var("COLUMNS").ok().and_then(|c| c.trim().parse().ok());
+    Some(columns.filter(|c| *c > 0).unwrap_or(window))
+}
+
+#[cfg(unix)]
+fn window_width() -> Option<usize> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: TIOCGWINSZ only writes a winsize to the pointer
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (result == 0 && size.ws_col > 0).then_some(size.ws_col as usize)
+}
+
+#[cfg(not(unix))]
+fn window_width() -> Option<usize> {
+    None
+}
@@ -0,0 +1,203 @@
+// OpenMetrics text exposition, for node_exporter's textfile collector or
+// anything else that scrapes Prometheus-style metrics.
+//
+// Only gauges are emitted. Fields that were not gathered leave their metric
+// family out instead of reporting a zero.
+
+use crate::info::profile::{Kind, Report};
+use crate::info::SystemInfo;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const PREFIX: &str = "hyperfetch";
+
+struct Family {
+    name: &'static str,
+    help: &'static str,
+    unit: Option<&'static str>,
+    samples: Vec<(Vec<(&'static str, String)>, f64)>,
+}
+
+impl Family {
+    fn new(name: &'static str, help: &'static str, unit: Option<&'static str>) -> Self {
+        Self {
+            name,
+            help,
+            unit,
+            samples: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, labels: Vec<(&'static str, String)>, value: f64) -> &mut Self {
+        self.samples.push((labels, value));
+        self
+    }
+
+    fn add_opt<T: Into<f64>>(&mut self, value: Option<T>) -> &mut Self {
+        if let Some(value) = value {
+            self.add(Vec::new(), value.into());
+        }
+        self
+    }
+}
+
+pub fn render(info: &SystemInfo, diagnostics: Option<&Report>) -> String {
+    let mut families = Vec::new();
+
+    // Descriptive fields go into labels of a constant 1 sample
+    let mut labels = Vec::new();
+    for (label, value) in [
+        ("hostname", &info.hostname),
+        ("os", &info.os),
+        ("os_id", &info.os_id),
+        ("kernel", &info.kernel),
+        ("cpu", &info.cpu),
+        ("cpu_arch", &info.cpu_arch),
+    ] {
+        if let Some(value) = value {
+            labels.push((label, value.clone()));
+        }
+    }
+    if !labels.is_empty() {
+        let mut family = Family::new("info", "System description, the value is always 1.", None);
+        family.add(labels, 1.0);
+        families.push(family);
+    }
+
+    // Lossless below 2^53 bytes, which is plenty
+    let bytes = |value: Option<u64>| value.map(|v| v as f64);
+
+    let mut family = Family::new("uptime_seconds", "Time since boot.", Some("seconds"));
+    family.add_opt(bytes(info.uptime_seconds));
+    families.push(family);
+
+    if let Some(load) = info.load_average {
+        for (name, help, value) in [
+            ("load1", "1 minute load average.", load[0]),
+            ("load5", "5 minute load average.", load[1]),
+            ("load15", "15 minute load average.", load[2]),
+        ] {
+            let mut family = Family::new(name, help, None);
+            family.add(Vec::new(), value);
+            families.push(family);
+        }
+    }
+
+    let mut family = Family::new("processes", "Number of processes.", None);
+    family.add_opt(info.processes);
+    families.push(family);
+
+    let mut family = Family::new("memory_used_bytes", "Memory in use.", Some("bytes"));
+    family.add_opt(bytes(info.memory_used));
+    families.push(family);
+    let mut family = Family::new("memory_total_bytes", "Total memory.", Some("bytes"));
+    family.add_opt(bytes(info.memory_total));
+    families.push(family);
+    let mut family = Family::new("swap_used_bytes", "Swap in use.", Some("bytes"));
+    family.add_opt(bytes(info.swap_used));
+    families.push(family);
+    let mut family = Family::new("swap_total_bytes", "Total swap.", Some("bytes"));
+    family.add_opt(bytes(info.swap_total));
+    families.push(family);
+
+    let mut size = Family::new("disk_size_bytes", "Size of the mounted filesystem.", Some("bytes"));
+    let mut used = Family::new("disk_used_bytes", "Space used on the mounted filesystem.", Some("bytes"));
+    let mut available = Family::new(
+        "disk_available_bytes",
+        "Space available to unprivileged users on the mounted filesystem.",
+        Some("bytes"),
+    );
+    for disk in &info.disks {
+        let labels = vec![("mount", disk.mount.clone()), ("filesystem", disk.filesystem.clone())];
+        size.add(labels.clone(), disk.size_bytes as f64);
+        used.add(labels.clone(), disk.used_bytes as f64);
+        available.add(labels, disk.available_bytes as f64);
+    }
+    families.extend([size, used, available]);
+
+    let mut family = Family::new("cpu_temperature_celsius", "CPU package temperature.", Some("celsius"));
+    family.add_opt(info.cpu_temp_celsius);
+    families.push(family);
+
+    let mut family = Family::new("gpu_temperature_celsius", "GPU temperature.", Some("celsius"));
+    for (index, gpu) in info.gpu.iter().enumerate() {
+        if let Some(temp) = gpu.temp_celsius {
+            family.add(vec![("index", index.to_string()), ("name", gpu.name.clone())], temp);
+        }
+    }
+    families.push(family);
+
+    let mut family = Family::new("battery_percent", "Battery charge in percent.", None);
+    family.add_opt(info.battery.as_ref().map(|b| b.percent));
+    families.push(family);
+
+    let mut family = Family::new("packages", "Installed packages per package manager.", None);
+    for count in &info.package_counts {
+        family.add(vec![("manager", count.manager.clone())], count.count as f64);
+    }
+    families.push(family);
+
+    if let Some(report) = diagnostics {
+        families.extend(diagnostic_families(report));
+    }
+
+    let mut out = String::new();
+    for family in families.iter().filter(|f| !f.samples.is_empty()) {
+        write_family(&mut out, family);
+    }
+    out.push_str("# EOF\n");
+    out
+}
+
+fn diagnostic_families(report: &Report) -> [Family; 2] {
+    let mut total = Family::new("fetch_duration_seconds", "Wall time of the whole fetch.", Some("seconds"));
+    total.add(Vec::new(), report.total_ms / 1000.0);
+
+    // The same command can run more than once, label sets have to be unique
+    let mut summed: BTreeMap<(&str, &str), f64> = BTreeMap::new();
+    for timing in &report.timings {
+        let kind = match timing.kind {
+            Kind::Probe => "probe",
+            Kind::Command => "command",
+        };
+        *summed.entry((kind, &timing.name)).or_default() += timing.ms / 1000.0;
+    }
+    let mut probes = Family::new(
+        "probe_duration_seconds",
+        "Time spent in each probe and external command.",
+        Some("seconds"),
+    );
+    for ((kind, name), seconds) in summed {
+        probes.add(vec![("kind", kind.to_string()), ("name", name.to_string())], seconds);
+    }
+
+    [total, probes]
+}
+
+fn write_family(out: &mut String, family: &Family) {
+    let name = format!("{}_{}", PREFIX, family.name);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    if let Some(unit) = family.unit {
+        let _ = writeln!(out, "# UNIT {} {}", name, unit);
+    }
+    let _ = writeln!(out, "# HELP {} {}", name, family.help);
+
+    for (labels, value) in &family.samples {
+        out.push_str(&name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+                .collect();
+            let _ = write!(out, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(out, " {}", value);
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', "\\\"")
+        .replace('\n', r"\n")
+}
@@ -1,3 +1,14 @@
+pub mod bar;
 pub mod colors;
+pub mod document;
+pub mod format;
+pub mod layout;
+pub mod metrics;
+pub mod modules;
+pub mod output;
+pub mod palette;
+pub mod svg;
+pub mod template;
+pub mod theme;
 
 pub use colors::*;
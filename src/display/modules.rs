@@ -0,0 +1,496 @@
+use crate::config::{BarMode, BarOptions, TemplateOptions};
+use crate::display::bar::{Bar, Scale};
+use crate::display::format;
+use crate::display::template::Template;
+use crate::info::SystemInfo;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Layout used when the config does not provide a `modules` list.
+pub const DEFAULT_MODULES: &[&str] = &[
+    // System
+    "os",
+    "kernel",
+    "hostname",
+    "uptime",
+    "machine_type",
+    "init_system",
+    "packages",
+    "shell",
+    "display_server",
+    "resolution",
+    "de",
+    "wm",
+    "theme",
+    "icons",
+    "cursor",
+    "terminal",
+    // Hardware
+    "sep",
+    "cpu",
+    "cpu_arch",
+    "cpu_cache",
+    "cpu_temp",
+    "cpu_governor",
+    "gpu",
+    "memory",
+    "swap",
+    "load_average",
+    "processes",
+    "disk",
+    "motherboard",
+    "bios",
+    // Network, power and audio
+    "sep",
+    "network",
+    "public_ip",
+    "battery",
+    "brightness",
+    "audio_device",
+    "volume",
+    // Misc
+    "sep",
+    "locale",
+    "timezone",
+    "boot_time",
+    "logged_users",
+    "virtualization",
+    "container",
+    "security",
+    "ssh_connection",
+    "bluetooth",
+    // Color bar
+    "sep",
+    "colors",
+];
+
+/// Headings for the Markdown and HTML reports with the modules under each,
+/// in the order the sections appear.
+pub const SECTIONS: &[(&str, &[&str])] = &[
+    (
+        "System",
+        &[
+            "os",
+            "kernel",
+            "hostname",
+            "uptime",
+            "machine_type",
+            "init_system",
+            "packages",
+            "shell",
+            "display_server",
+            "resolution",
+            "de",
+            "wm",
+            "wm_theme",
+            "theme",
+            "icons",
+            "cursor",
+            "terminal",
+        ],
+    ),
+    (
+        "Hardware",
+        &[
+            "cpu",
+            "cpu_arch",
+            "cpu_cache",
+            "cpu_temp",
+            "cpu_governor",
+            "gpu",
+            "memory",
+            "swap",
+            "load_average",
+            "processes",
+            "disk",
+            "motherboard",
+            "bios",
+        ],
+    ),
+    ("Network", &["network", "public_ip"]),
+    ("Power", &["battery", "brightness"]),
+    (
+        "Misc",
+        &[
+            "audio_device",
+            "volume",
+            "locale",
+            "timezone",
+            "boot_time",
+            "logged_users",
+            "virtualization",
+            "container",
+            "security",
+            "ssh_connection",
+            "bluetooth",
+        ],
+    ),
+];
+
+pub enum InfoLine {
+    // `bar` is set when `value` shows a usage bar
+    Field {
+        label: String,
+        value: String,
+        bar: Option<Bar>,
+    },
+    Title { user: String, host: String },
+    Underline,
+    Rule,
+    Blank,
+    Colors,
+}
+
+enum Source {
+    // One line when the field is present. Object fields (battery, public_ip)
+    // expose their members as placeholders.
+    Field(&'static str),
+    // One line per element of an array field, skipping elements that lack
+    // the optional required key.
+    Each(&'static str, Option<&'static str>),
+}
+
+struct Module {
+    name: &'static str,
+    label: &'static str,
+    value: &'static str,
+    source: Source,
+    // Modules with a `{bar}`, and their value when it replaces the numbers
+    bar: Option<(Scale, &'static str)>,
+}
+
+const fn field(name: &'static str, label: &'static str, value: &'static str) -> Module {
+    Module {
+        name,
+        label,
+        value,
+        source: Source::Field(name),
+        bar: None,
+    }
+}
+
+impl Module {
+    const fn with_bar(self, scale: Scale, value: &'static str) -> Module {
+        Module {
+            bar: Some((scale, value)),
+            ..self
+        }
+    }
+}
+
+const MODULES: &[Module] = &[
+    // System
+    field("os", "OS", "{os}"),
+    field("kernel", "Kernel", "{kernel}"),
+    field("hostname", "Host", "{hostname}"),
+    field("uptime", "Uptime", "{uptime}"),
+    field("machine_type", "Machine", "{machine_type}"),
+    field("init_system", "Init", "{init_system}"),
+    field("packages", "Packages", "{packages}"),
+    field("shell", "Shell", "{shell}{? {shell_version}}"),
+    field("display_server", "Display", "{display_server}"),
+    field("resolution", "Resolution", "{resolution}"),
+    field("de", "DE", "{de}"),
+    field("wm", "WM", "{wm}"),
+    field("wm_theme", "WM Theme", "{wm_theme}"),
+    field("theme", "Theme", "{theme}"),
+    field("icons", "Icons", "{icons}"),
+    field("cursor", "Cursor", "{cursor}"),
+    field("terminal", "Terminal", "{terminal}{? ({terminal_font})}"),
+    // Hardware
+    field("cpu", "CPU", "{cpu} ({cpu_threads}) @ {cpu_freq}"),
+    field("cpu_arch", "Arch", "{cpu_arch}"),
+    field("cpu_cache", "Cache", "{cpu_cache}"),
+    field("cpu_temp", "CPU Temp", "{cpu_temp}"),
+    field("cpu_governor", "Governor", "{cpu_governor}"),
+    Module {
+        name: "gpu",
+        label: "GPU",
+        value: "{name}{? [{driver}]}{? ({vram})}{? @ {temp}}",
+        source: Source::Each("gpu", None),
+        bar: None,
+    },
+    field("memory", "Memory", "{memory}").with_bar(Scale::Usage, "{bar} {percent}%"),
+    field("swap", "Swap", "{swap}").with_bar(Scale::Usage, "{bar} {percent}%"),
+    field("load_average", "Load", "{load_average}"),
+    field("processes", "Processes", "{processes}"),
+    Module {
+        name: "disk",
+        label: "Disk ({mount})",
+        value: "{used} / {size} ({percent}%) [{filesystem}{?, {disk_type}}]",
+        source: Source::Each("disks", None),
+        bar: Some((Scale::Usage, "{bar} {percent}% [{filesystem}{?, {disk_type}}]")),
+    },
+    field("motherboard", "Board", "{motherboard}"),
+    field("bios", "BIOS", "{bios}"),
+    // Network
+    Module {
+        name: "network",
+        label: "Net ({name})",
+        value: "{details}",
+        source: Source::Each("interfaces", Some("details")),
+        bar: None,
+    },
+    field("public_ip", "Public IP", "{ip}{? ({location})}{? [{isp}]}"),
+    // Power
+    field("battery", "Battery", "{percent}% ({status}){? ~{time_remaining}}")
+        .with_bar(Scale::Charge, "{bar} {percent}% ({status}){? ~{time_remaining}}"),
+    field("brightness", "Brightness", "{brightness}").with_bar(Scale::Level, "{bar} {percent}%"),
+    // Audio
+    field("audio_device", "Audio", "{audio_device}"),
+    field("volume", "Volume", "{volume}"),
+    // Misc
+    field("locale", "Locale", "{locale}"),
+    field("timezone", "Timezone", "{timezone}"),
+    field("boot_time", "Boot Time", "{boot_time}"),
+    field("logged_users", "Users", "{logged_users}"),
+    field("virtualization", "Virt", "{virtualization}"),
+    field("container", "Container", "{container}"),
+    field("security", "Security", "{security}"),
+    field("ssh_connection", "SSH", "{ssh_connection}"),
+    field("bluetooth", "Bluetooth", "{bluetooth}"),
+];
+
+pub fn default_modules() -> Vec<String> {
+    DEFAULT_MODULES.iter().map(|m| m.to_string()).collect()
+}
+
+pub fn collect_lines(
+    info: &SystemInfo,
+    modules: &[String],
+    templates: &HashMap<String, TemplateOptions>,
+    bars: &BarOptions,
+) -> Vec<InfoLine> {
+    let root = match serde_json::to_value(info) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    let derived = derived_fields(info);
+
+    let mut lines = Vec::new();
+    for module in modules {
+        let module = module.trim();
+        match module {
+            "title" => {
+                let user = users::get_current_username()
+                    .map(|u| u.to_string_lossy().to_string())
+                    .or_else(|| std::env::var("USER").ok())
+                    .unwrap_or_else(|| "user".to_string());
+                let host = info.hostname.clone().unwrap_or_else(|| "localhost".to_string());
+                lines.push(InfoLine::Title { user, host });
+            }
+            "underline" => lines.push(InfoLine::Underline),
+            "line" => lines.push(InfoLine::Rule),
+            "sep" => lines.push(InfoLine::Blank),
+            "colors" => lines.push(InfoLine::Colors),
+            // Unknown modules are ignored so configs stay portable across versions
+            _ => {
+                if let Some(def) = MODULES.iter().find(|m| m.name == module) {
+                    push_module(&mut lines, def, &derived, &root, templates.get(module), bars);
+                }
+            }
+        }
+    }
+    lines
+}
+
+fn push_module(
+    lines: &mut Vec<InfoLine>,
+    def: &Module,
+    derived: &Map<String, Value>,
+    root: &Map<String, Value>,
+    custom: Option<&TemplateOptions>,
+    bars: &BarOptions,
+) {
+    let default_value = match (def.bar, bars.mode) {
+        (Some(_), BarMode::Beside) => format!("{} {{bar}}", def.value),
+        (Some((_, value)), BarMode::Instead) => value.to_string(),
+        _ => def.value.to_string(),
+    };
+    let label = Template::parse(custom.and_then(|c| c.label.as_deref()).unwrap_or(def.label));
+    let value = Template::parse(custom.and_then(|c| c.value.as_deref()).unwrap_or(&default_value));
+
+    match def.source {
+        Source::Field(key) => {
+            let item = match derived.get(key).or_else(|| root.get(key)) {
+                None | Some(Value::Null) => return,
+                Some(Value::Object(map)) => with_extras(def.name, map.clone()),
+                Some(_) => Map::new(),
+            };
+            let (item, bar) = with_bar(def, item, root, bars);
+            push_rendered(lines, &label, &value, &[&item, derived, root], bar);
+        }
+        Source::Each(key, required) => {
+            let Some(Value::Array(items)) = root.get(key) else {
+                return;
+            };
+            for item in items {
+                let Value::Object(map) = item else {
+                    continue;
+                };
+                let item = with_extras(def.name, map.clone());
+                if required.is_some_and(|k| !item.contains_key(k)) {
+                    continue;
+                }
+                let (item, bar) = with_bar(def, item, root, bars);
+                push_rendered(lines, &label, &value, &[&item, derived, root], bar);
+            }
+        }
+    }
+}
+
+fn push_rendered(
+    lines: &mut Vec<InfoLine>,
+    label: &Template,
+    value: &Template,
+    layers: &[&Map<String, Value>],
+    bar: Option<Bar>,
+) {
+    let lookup = |name: &str| {
+        layers
+            .iter()
+            .find_map(|layer| layer.get(name))
+            .and_then(value_to_string)
+    };
+    let value = value.render(lookup);
+    lines.push(InfoLine::Field {
+        label: label.render(lookup),
+        bar: bar.filter(|bar| value.contains(&bar.text)),
+        value,
+    });
+}
+
+// `{bar}` and `{percent}` for the modules with a usage bar
+fn with_bar(
+    def: &Module,
+    mut item: Map<String, Value>,
+    root: &Map<String, Value>,
+    options: &BarOptions,
+) -> (Map<String, Value>, Option<Bar>) {
+    let Some((scale, _)) = def.bar else {
+        return (item, None);
+    };
+    let ratio = |map: &Map<String, Value>, used: &str, total: &str| {
+        let used = map.get(used).and_then(Value::as_f64)?;
+        let total = map.get(total).and_then(Value::as_f64).filter(|t| *t > 0.0)?;
+        Some(used / total * 100.0)
+    };
+    let percent = match def.name {
+        "memory" => ratio(root, "memory_used", "memory_total"),
+        "swap" => ratio(root, "swap_used", "swap_total"),
+        "disk" => ratio(&item, "used_bytes", "size_bytes"),
+        "brightness" => root.get("brightness_percent").and_then(Value::as_f64),
+        _ => None,
+    }
+    .or_else(|| item.get("percent").and_then(Value::as_f64));
+    let Some(percent) = percent else {
+        return (item, None);
+    };
+
+    let bar = Bar::new(percent, scale, options);
+    item.insert("bar".to_string(), Value::String(bar.text.clone()));
+    if !item.contains_key("percent") {
+        item.insert("percent".to_string(), Value::from(percent.round() as u64));
+    }
+    (item, Some(bar))
+}
+
+/// Display text for the raw numeric fields (`memory`, `uptime`, ...), looked
+/// up before the fields themselves.
+pub fn derived_fields(info: &SystemInfo) -> Map<String, Value> {
+    let mut fields = Map::new();
+    let mut insert = |key: &str, value: Option<String>| {
+        if let Some(value) = value {
+            fields.insert(key.to_string(), Value::String(value));
+        }
+    };
+
+    insert("uptime", info.uptime_seconds.map(format::uptime));
+    insert("load_average", info.load_average.as_ref().map(format::load));
+    insert("cpu_freq", info.cpu_freq_mhz.map(format::frequency));
+    insert("cpu_temp", info.cpu_temp_celsius.map(format::temperature));
+    insert("memory", info.memory_used.zip(info.memory_total).map(|(u, t)| format::usage(u, t)));
+    insert("swap", info.swap_used.zip(info.swap_total).map(|(u, t)| format::usage(u, t)));
+    insert("brightness", info.brightness_percent.map(|p| format!("{}%", p)));
+    insert(
+        "volume",
+        info.volume_percent.map(|p| format::volume(p, info.volume_muted.unwrap_or(false))),
+    );
+
+    fields
+}
+
+// Derived placeholders that are awkward to express as templates
+fn with_extras(module: &str, mut item: Map<String, Value>) -> Map<String, Value> {
+    let text = |map: &Map<String, Value>, key: &str| map.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let number = |map: &Map<String, Value>, key: &str| map.get(key).and_then(Value::as_u64);
+
+    match module {
+        "disk" => {
+            for key in ["size", "used", "available"] {
+                if let Some(bytes) = number(&item, &format!("{}_bytes", key)) {
+                    item.insert(key.to_string(), Value::String(format::bytes(bytes)));
+                }
+            }
+        }
+        "gpu" => {
+            if let Some(bytes) = number(&item, "vram_bytes") {
+                item.insert("vram".to_string(), Value::String(format::mebibytes(bytes)));
+            }
+            if let Some(celsius) = item.get("temp_celsius").and_then(Value::as_f64) {
+                item.insert("temp".to_string(), Value::String(format::temperature(celsius)));
+            }
+        }
+        "battery" => {
+            if let Some(minutes) = number(&item, "time_remaining_minutes") {
+                let text = format::duration_minutes(minutes as u32);
+                item.insert("time_remaining".to_string(), Value::String(text));
+            }
+        }
+        "network" => {
+            let mut parts = Vec::new();
+            for key in ["ipv4", "ipv6", "speed"] {
+                if let Some(value) = text(&item, key) {
+                    parts.push(value);
+                }
+            }
+            if let Some(state) = text(&item, "state") {
+                if state != "up" {
+                    parts.push(format!("[{}]", state));
+                }
+            }
+            if !parts.is_empty() {
+                item.insert("details".to_string(), Value::String(parts.join(", ")));
+            }
+        }
+        "public_ip" => {
+            let location: Vec<String> = ["city", "region", "country", "zip"]
+                .iter()
+                .filter_map(|key| text(&item, key))
+                .collect();
+            if !location.is_empty() {
+                item.insert("location".to_string(), Value::String(location.join(", ")));
+            }
+        }
+        _ => {}
+    }
+
+    item
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Array(items) => {
+            let parts: Vec<String> = items.iter().filter_map(value_to_string).collect();
+            if parts.is_empty() {
+                None
+            } else {
+                Some(parts.join(", "))
+            }
+        }
+        Value::Null | Value::Object(_) => None,
+    }
+}
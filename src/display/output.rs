@@ -0,0 +1,287 @@
+// Machine-readable output.
+//
+// Every format is produced from the JSON serialization of `SystemInfo`, so
+// they all contain the same fields: the ones the `[info]` toggles gathered.
+// OpenMetrics is the exception, it picks the numeric fields and is built in
+// `metrics`. Markdown and HTML show the display text of the info lines
+// instead and are built in `document`, SVG draws the terminal rendering.
+
+use crate::ascii::AsciiArt;
+use crate::display::{document, metrics, svg, DisplayConfig};
+use crate::info::profile::Report;
+use crate::info::SystemInfo;
+use crate::schema::SCHEMA_VERSION;
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+    /// `HYPERFETCH_*=value` lines for `eval` in a shell
+    Env,
+    /// Prometheus gauges, for node_exporter's textfile collector
+    #[value(name = "openmetrics")]
+    OpenMetrics,
+    /// Tables grouped by section, for issues and wiki pages
+    Markdown,
+    /// A self-contained page with the logo next to the tables
+    Html,
+    /// An image of the fetch as the terminal shows it
+    Svg,
+}
+
+pub fn print_output(info: &SystemInfo, format: Format, diagnostics: Option<&Report>) {
+    match render(info, format, diagnostics) {
+        Ok(text) => print!("{}", text),
+        Err(e) => eprintln!("Error serializing output: {}", e),
+    }
+}
+
+pub fn render(info: &SystemInfo, format: Format, diagnostics: Option<&Report>) -> Result<String, String> {
+    match format {
+        Format::Json => serde_json::to_string_pretty(&to_value(info, diagnostics)?)
+            .map(|json| json + "\n")
+            .map_err(|e| e.to_string()),
+        Format::Toml => {
+            // TOML has no null, a missing key means the same
+            let mut value = to_value(info, diagnostics)?;
+            remove_nulls(&mut value);
+            toml::to_string_pretty(&value).map_err(|e| e.to_string())
+        }
+        Format::Yaml => Ok(to_yaml(&to_value(info, diagnostics)?)),
+        Format::Env => Ok(to_env(&to_value(info, diagnostics)?)),
+        Format::OpenMetrics => Ok(metrics::render(info, diagnostics)),
+        // The default layout, the binary passes the configured one to `document`
+        Format::Markdown => Ok(document::markdown(info, &DisplayConfig::default())),
+        Format::Html => {
+            let ascii = AsciiArt::for_distro(info.os_id.as_deref(), false);
+            Ok(document::html(info, &ascii, &DisplayConfig::default()))
+        }
+        Format::Svg => {
+            let ascii = AsciiArt::for_distro(info.os_id.as_deref(), false);
+            Ok(svg::render(info, &ascii, &DisplayConfig::default()))
+        }
+    }
+}
+
+// Fields in struct order (serde_json is built with `preserve_order`), with
+// schema_version first and the diagnostics last
+fn to_value(info: &SystemInfo, diagnostics: Option<&Report>) -> Result<Value, String> {
+    let Value::Object(fields) = serde_json::to_value(info).map_err(|e| e.to_string())? else {
+        return Err("SystemInfo did not serialize to an object".to_string());
+    };
+    let mut map = Map::new();
+    map.insert("schema_version".to_string(), Value::from(SCHEMA_VERSION));
+    map.extend(fields);
+    if let Some(report) = diagnostics {
+        map.insert(
+            "diagnostics".to_string(),
+            serde_json::to_value(report).unwrap_or_default(),
+        );
+    }
+    Ok(Value::Object(map))
+}
+
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(items) => {
+            items.retain(|v| !v.is_null());
+            items.iter_mut().for_each(remove_nulls);
+        }
+        _ => {}
+    }
+}
+
+fn to_yaml(value: &Value) -> String {
+    let mut out = String::new();
+    match value {
+        Value::Object(map) if !map.is_empty() => write_yaml_map(&mut out, map, 0),
+        Value::Array(items) if !items.is_empty() => write_yaml_list(&mut out, items, 0),
+        _ => {
+            out.push_str(&yaml_scalar(value));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn write_yaml_map(out: &mut String, map: &Map<String, Value>, indent: usize) {
+    for (key, value) in map {
+        out.push_str(&" ".repeat(indent));
+        out.push_str(&yaml_string(key));
+        out.push(':');
+        write_yaml_value(out, value, indent);
+    }
+}
+
+fn write_yaml_list(out: &mut String, items: &[Value], indent: usize) {
+    for item in items {
+        out.push_str(&" ".repeat(indent));
+        out.push('-');
+        match item {
+            // The first key goes on the dash line, the rest line up below it
+            Value::Object(map) if !map.is_empty() => {
+                let mut nested = String::new();
+                write_yaml_map(&mut nested, map, indent + 2);
+                out.push(' ');
+                out.push_str(&nested[indent + 2..]);
+            }
+            _ => write_yaml_value(out, item, indent),
+        }
+    }
+}
+
+// Writes what follows "key:" or "-", including the line break
+fn write_yaml_value(out: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            write_yaml_map(out, map, indent + 2);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            write_yaml_list(out, items, indent + 2);
+        }
+        _ => {
+            out.push(' ');
+            out.push_str(&yaml_scalar(value));
+            out.push('\n');
+        }
+    }
+}
+
+fn yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => yaml_string(s),
+        Value::Array(_) => "[]".to_string(),
+        Value::Object(_) => "{}".to_string(),
+    }
+}
+
+// Plain when it cannot be mistaken for anything else, double-quoted otherwise.
+// YAML double-quoted strings accept JSON escapes.
+fn yaml_string(s: &str) -> String {
+    const RESERVED: &[&str] = &["null", "true", "false", "yes", "no", "on", "off", "y", "n", "~"];
+
+    let plain = !s.is_empty()
+        && s.chars().all(|c| c.is_alphanumeric() || " _-./()@+,%°".contains(c))
+        && !s.starts_with([' ', '-', '+', '.', '@', '%'])
+        && !s.ends_with(' ')
+        && !s.starts_with(|c: char| c.is_ascii_digit())
+        && !RESERVED.contains(&s.to_lowercase().as_str());
+
+    if plain {
+        s.to_string()
+    } else {
+        Value::String(s.to_string()).to_string()
+    }
+}
+
+fn to_env(value: &Value) -> String {
+    let mut lines = Vec::new();
+    flatten_env(&mut lines, "HYPERFETCH".to_string(), value);
+    lines.iter().map(|line| line.to_string() + "\n").collect()
+}
+
+// Objects become NAME_FIELD, arrays NAME_0, NAME_1, ... plus NAME_COUNT
+fn flatten_env(lines: &mut Vec<String>, name: String, value: &Value) {
+    match value {
+        Value::Null => {}
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten_env(lines, format!("{}_{}", name, env_key(key)), value);
+            }
+        }
+        Value::Array(items) => {
+            lines.push(format!("{}_COUNT={}", name, items.len()));
+            for (i, item) in items.iter().enumerate() {
+                flatten_env(lines, format!("{}_{}", name, i), item);
+            }
+        }
+        Value::String(s) => lines.push(format!("{}={}", name, shell_quote(s))),
+        Value::Bool(_) | Value::Number(_) => lines.push(format!("{}={}", name, value)),
+    }
+}
+
+fn env_key(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+// Single quotes keep everything literal, a quote inside is closed, escaped and reopened
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reserved_words_are_quoted() {
+        for word in ["null", "true", "False", "yes", "NO", "on", "off", "y", "n", "~"] {
+            assert_eq!(yaml_string(word), format!("\"{}\"", word));
+        }
+        assert_eq!(yaml_string("nullable"), "nullable");
+    }
+
+    #[test]
+    fn leading_digits_and_indicators_are_quoted() {
+        assert_eq!(yaml_string("6.1.0-18-amd64"), "\"6.1.0-18-amd64\"");
+        assert_eq!(yaml_string("1"), "\"1\"");
+        assert_eq!(yaml_string("-rc1"), "\"-rc1\"");
+        assert_eq!(yaml_string(".hidden"), "\".hidden\"");
+        assert_eq!(yaml_string(" padded"), "\" padded\"");
+        assert_eq!(yaml_string("padded "), "\"padded \"");
+        assert_eq!(yaml_string(""), "\"\"");
+        assert_eq!(yaml_string("Debian GNU/Linux 12 (bookworm)"), "Debian GNU/Linux 12 (bookworm)");
+    }
+
+    #[test]
+    fn colons_and_hashes_are_quoted() {
+        assert_eq!(yaml_string("key: value"), "\"key: value\"");
+        assert_eq!(yaml_string("a:b"), "\"a:b\"");
+        assert_eq!(yaml_string("GPU #1"), "\"GPU #1\"");
+        assert_eq!(yaml_string("say \"hi\"\n"), "\"say \\\"hi\\\"\\n\"");
+    }
+
+    #[test]
+    fn empty_and_nested_collections() {
+        let value = json!({
+            "gpu": [],
+            "ttl": {},
+            "disks": [{"mount": "/", "types": ["ssd", "nvme"]}, {"mount": "/home", "types": []}],
+            "load": [[0.5, 1.0], []],
+        });
+        let expected = "\
+gpu: []
+ttl: {}
+disks:
+  - mount: /
+    types:
+      - ssd
+      - nvme
+  - mount: /home
+    types: []
+load:
+  -
+    - 0.5
+    - 1.0
+  - []
+";
+        assert_eq!(to_yaml(&value), expected);
+        assert_eq!(to_yaml(&json!({})), "{}\n");
+        assert_eq!(to_yaml(&json!([])), "[]\n");
+    }
+}
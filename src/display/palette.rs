@@ -0,0 +1,230 @@
+// Color values from the config and logo palettes, and their escape codes.
+//
+// Colors are written as names (`cyan`, `bright_red`), `#RRGGBB` or `#RGB`,
+// `rgb(R, G, B)` or a 256-color index. Indices 0-15 are the named colors,
+// which the terminal theme defines; everything else is kept as RGB and
+// reduced to the nearest color the terminal can show when it is printed.
+
+use crate::display::theme::Style;
+use colored::Color;
+use std::sync::OnceLock;
+
+/// How many colors the terminal shows, from `COLORTERM` and `TERM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Depth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+const NAMED: &[(&str, Color)] = &[
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("purple", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("white", Color::White),
+    ("bright_black", Color::BrightBlack),
+    ("bright_red", Color::BrightRed),
+    ("bright_green", Color::BrightGreen),
+    ("bright_yellow", Color::BrightYellow),
+    ("bright_blue", Color::BrightBlue),
+    ("bright_magenta", Color::BrightMagenta),
+    ("bright_cyan", Color::BrightCyan),
+    ("bright_white", Color::BrightWhite),
+];
+
+// Indices 0-15 in order
+const ANSI: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+    Color::BrightBlack,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+    Color::BrightWhite,
+];
+
+// xterm's values for the 16 colors, to pick the nearest one
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+// Channel levels of the 6x6x6 cube at indices 16-231
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The color for a config or palette value, None if it is not one.
+pub fn parse(value: &str) -> Option<Color> {
+    let value = value.trim().to_lowercase();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        let digits: Vec<u8> = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<_>>()?;
+        return match digits[..] {
+            [r, g, b] => Some(rgb(r * 17, g * 17, b * 17)),
+            [r1, r2, g1, g2, b1, b2] => Some(rgb(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2)),
+            _ => None,
+        };
+    }
+
+    if let Some(args) = value.strip_prefix("rgb(").and_then(|v| v.strip_suffix(')')) {
+        let channels: Vec<u8> = args
+            .split(',')
+            .map(|c| c.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        return match channels[..] {
+            [r, g, b] => Some(rgb(r, g, b)),
+            _ => None,
+        };
+    }
+
+    if let Ok(index) = value.parse::<u8>() {
+        return Some(from_index(index));
+    }
+
+    // "bright_red", "brightred" and "bright-red" are the same
+    let name = value.replace(['-', '_'], "");
+    NAMED
+        .iter()
+        .find(|(known, _)| known.replace('_', "") == name)
+        .map(|(_, color)| *color)
+}
+
+/// A 256-color index as a color.
+pub fn from_index(index: u8) -> Color {
+    match index {
+        0..=15 => ANSI[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            rgb(CUBE[(i / 36) as usize], CUBE[(i / 6 % 6) as usize], CUBE[(i % 6) as usize])
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            rgb(level, level, level)
+        }
+    }
+}
+
+fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::TrueColor { r, g, b }
+}
+
+pub fn depth() -> Depth {
+    static DEPTH: OnceLock<Depth> = OnceLock::new();
+    *DEPTH.get_or_init(|| {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default().to_lowercase();
+        let term = std::env::var("TERM").unwrap_or_default().to_lowercase();
+        detect(&colorterm, &term)
+    })
+}
+
+fn detect(colorterm: &str, term: &str) -> Depth {
+    if colorterm == "truecolor" || colorterm == "24bit" || term.ends_with("-direct") || term.ends_with("-truecolor") {
+        Depth::TrueColor
+    } else if term.contains("256color") {
+        Depth::Ansi256
+    } else {
+        Depth::Ansi16
+    }
+}
+
+/// `text` with escape codes for `style`, its colors reduced to what the
+/// terminal shows. Plain when colors are turned off.
+pub fn paint(text: &str, style: &Style) -> String {
+    if !colored::control::SHOULD_COLORIZE.should_colorize() {
+        return text.to_string();
+    }
+
+    let mut codes = Vec::new();
+    for (enabled, code) in [(style.bold, "1"), (style.dim, "2"), (style.italic, "3")] {
+        if enabled {
+            codes.push(code.to_string());
+        }
+    }
+    if let Some(color) = style.color {
+        codes.push(code(color, false, depth()));
+    }
+    if let Some(background) = style.background {
+        codes.push(code(background, true, depth()));
+    }
+    if codes.is_empty() {
+        return text.to_string();
+    }
+    format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+}
+
+fn code(color: Color, background: bool, depth: Depth) -> String {
+    let base = if background { 40 } else { 30 };
+    let extended = if background { 48 } else { 38 };
+
+    let Color::TrueColor { r, g, b } = color else {
+        let index = ANSI.iter().position(|c| *c == color).unwrap_or(7) as u8;
+        return match index {
+            0..=7 => (base + index).to_string(),
+            _ => (base + 60 + index - 8).to_string(),
+        };
+    };
+
+    match depth {
+        Depth::TrueColor => format!("{};2;{};{};{}", extended, r, g, b),
+        Depth::Ansi256 => format!("{};5;{}", extended, nearest_256(r, g, b)),
+        Depth::Ansi16 => code(ANSI[nearest_16(r, g, b)], background, depth),
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn nearest_16(r: u8, g: u8, b: u8) -> usize {
+    (0..16).min_by_key(|i| distance(ANSI_RGB[*i], (r, g, b))).unwrap_or(7)
+}
+
+// The closer of the nearest cube color and the nearest gray, 0-15 are left
+// out since the terminal theme decides what they look like
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| (0..6).min_by_key(|i| (CUBE[*i] as i32 - c as i32).abs()).unwrap_or(0);
+    let (cr, cg, cb) = (level(r), level(g), level(b));
+    let cube = 16 + 36 * cr as u8 + 6 * cg as u8 + cb as u8;
+    let cube_rgb = (CUBE[cr], CUBE[cg], CUBE[cb]);
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + gray * 10;
+
+    if distance((gray_level, gray_level, gray_level), (r, g, b)) < distance(cube_rgb, (r, g, b)) {
+        232 + gray
+    } else {
+        cube
+    }
+}
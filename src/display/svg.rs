@@ -0,0 +1,106 @@
+// SVG image of the terminal rendering, to share a fetch from servers and CI
+// without a terminal or a screenshot tool.
+//
+// The spans of `styled_info` are placed on a character grid, every span at
+// its own column, so the image lines up like the terminal in any monospace
+// font. Columns are terminal columns, a wide character takes two. Colors come from the same dark theme as the HTML report.
+
+use crate::ascii::AsciiArt;
+use crate::display::colors::{styled_info, DisplayConfig};
+use crate::display::document::{css_color, escape_html};
+use crate::display::layout;
+use crate::info::SystemInfo;
+use std::fmt::Write;
+
+const FONT: &str = "ui-monospace, 'DejaVu Sans Mono', Menlo, Consolas, monospace";
+const FONT_SIZE: f64 = 14.0;
+// 0.6em, the advance of common monospace fonts
+const CELL_WIDTH: f64 = 8.4;
+const LINE_HEIGHT: f64 = 17.0;
+const PADDING: f64 = 20.0;
+const BACKGROUND: &str = "#1e1e1e";
+const FOREGROUND: &str = "#cccccc";
+
+pub fn render(info: &SystemInfo, ascii: &AsciiArt, config: &DisplayConfig) -> String {
+    let lines = styled_info(info, ascii, config);
+    let columns = lines
+        .iter()
+        .map(|line| line.iter().map(|span| layout::width(&span.text)).sum::<usize>())
+        .max()
+        .unwrap_or(0);
+    let width = columns as f64 * CELL_WIDTH + 2.0 * PADDING;
+    let height = lines.len() as f64 * LINE_HEIGHT + 2.0 * PADDING;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.1}\" height=\"{h:.1}\" viewBox=\"0 0 {w:.1} {h:.1}\" \
+         font-family=\"{font}\" font-size=\"{size}\" xml:space=\"preserve\">",
+        w = width,
+        h = height,
+        font = FONT,
+        size = FONT_SIZE
+    );
+    let _ = writeln!(out, "<rect width=\"100%\" height=\"100%\" rx=\"8\" fill=\"{}\"/>", BACKGROUND);
+
+    for (row, line) in lines.iter().enumerate() {
+        let top = PADDING + row as f64 * LINE_HEIGHT;
+        let mut column = 0;
+        let mut text = String::new();
+
+        for span in line {
+            let len = layout::width(&span.text);
+            let x = PADDING + column as f64 * CELL_WIDTH;
+            column += len;
+
+            if let Some(background) = span.style.background.filter(|_| config.use_colors) {
+                let _ = writeln!(
+                    out,
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+                    x,
+                    top,
+                    len as f64 * CELL_WIDTH,
+                    LINE_HEIGHT,
+                    css_color(background)
+                );
+            }
+
+            // Leading spaces become part of the position, blank spans are left out
+            let visible = span.text.trim_start();
+            if visible.trim_end().is_empty() {
+                continue;
+            }
+            let x = x + (len - layout::width(visible)) as f64 * CELL_WIDTH;
+            let fill = match span.style.color.filter(|_| config.use_colors) {
+                Some(color) => css_color(color),
+                None => FOREGROUND.to_string(),
+            };
+            let mut attributes = String::new();
+            if span.style.bold {
+                attributes.push_str(" font-weight=\"bold\"");
+            }
+            if span.style.italic {
+                attributes.push_str(" font-style=\"italic\"");
+            }
+            if span.style.dim {
+                attributes.push_str(" opacity=\"0.6\"");
+            }
+            let _ = write!(
+                text,
+                "<tspan x=\"{:.1}\" fill=\"{}\"{}>{}</tspan>",
+                x,
+                fill,
+                attributes,
+                escape_html(visible)
+            );
+        }
+
+        if !text.is_empty() {
+            // Baseline at roughly 80% of the line
+            let _ = writeln!(out, "<text y=\"{:.1}\">{}</text>", top + LINE_HEIGHT * 0.8, text);
+        }
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
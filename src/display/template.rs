@@ -0,0 +1,136 @@
+// Format templates for info lines.
+//
+// `{name}` is replaced by the value of `name`, or `?` when it is missing.
+// `{?...}` is a conditional segment: it is only rendered when every
+// placeholder inside it has a value. Segments can be nested.
+// `{{` produces a literal brace; `}}` does too, outside conditional segments.
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Field(String),
+    Optional(Vec<Token>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    tokens: Vec<Token>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Self {
+        let chars: Vec<char> = source.chars().collect();
+        let mut pos = 0;
+        let tokens = parse_tokens(&chars, &mut pos, false);
+        Self { tokens }
+    }
+
+    pub fn render<F>(&self, lookup: F) -> String
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut out = String::new();
+        render_tokens(&self.tokens, &lookup, &mut out, true);
+        out
+    }
+}
+
+fn parse_tokens(chars: &[char], pos: &mut usize, nested: bool) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+
+    while *pos < chars.len() {
+        let ch = chars[*pos];
+        let next = chars.get(*pos + 1).copied();
+
+        match (ch, next) {
+            ('{', Some('{')) => {
+                text.push(ch);
+                *pos += 2;
+            }
+            ('}', Some('}')) if !nested => {
+                text.push(ch);
+                *pos += 2;
+            }
+            ('{', Some('?')) => {
+                flush_text(&mut tokens, &mut text);
+                *pos += 2;
+                let inner = parse_tokens(chars, pos, true);
+                tokens.push(Token::Optional(inner));
+            }
+            ('{', _) => match read_field(chars, *pos + 1) {
+                Some((name, end)) => {
+                    flush_text(&mut tokens, &mut text);
+                    tokens.push(Token::Field(name));
+                    *pos = end + 1;
+                }
+                None => {
+                    text.push(ch);
+                    *pos += 1;
+                }
+            },
+            ('}', _) if nested => {
+                *pos += 1;
+                flush_text(&mut tokens, &mut text);
+                return tokens;
+            }
+            _ => {
+                text.push(ch);
+                *pos += 1;
+            }
+        }
+    }
+
+    flush_text(&mut tokens, &mut text);
+    tokens
+}
+
+fn read_field(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut name = String::new();
+    for (offset, ch) in chars[start..].iter().enumerate() {
+        match ch {
+            '}' if !name.is_empty() => return Some((name, start + offset)),
+            c if c.is_ascii_alphanumeric() || *c == '_' || *c == '.' => name.push(*c),
+            _ => return None,
+        }
+    }
+    None
+}
+
+fn flush_text(tokens: &mut Vec<Token>, text: &mut String) {
+    if !text.is_empty() {
+        tokens.push(Token::Text(std::mem::take(text)));
+    }
+}
+
+// Returns false when a placeholder in this segment (outside nested
+// conditionals) had no value.
+fn render_tokens<F>(tokens: &[Token], lookup: &F, out: &mut String, top_level: bool) -> bool
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut complete = true;
+
+    for token in tokens {
+        match token {
+            Token::Text(text) => out.push_str(text),
+            Token::Field(name) => match lookup(name) {
+                Some(value) => out.push_str(&value),
+                None => {
+                    complete = false;
+                    if top_level {
+                        out.push('?');
+                    }
+                }
+            },
+            Token::Optional(inner) => {
+                let mut segment = String::new();
+                if render_tokens(inner, lookup, &mut segment, false) {
+                    out.push_str(&segment);
+                }
+            }
+        }
+    }
+
+    complete
+}
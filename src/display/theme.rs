@@ -0,0 +1,191 @@
+// Styles of the info lines, from built-in themes, theme files and [theme].
+//
+// A theme gives each element (label, separator, value, title, underline,
+// color bar) a foreground, background, bold, italic and dim. The built-in
+// themes are the TOML files in `themes/`. User themes are files of the same
+// layout in ~/.config/hyperfetch/themes/, or any path ending in `.toml`.
+//
+// Themes are layered: the default theme, the named theme (and the theme it
+// names in turn), then the fields set under [theme] in the config.
+
+use crate::config::{Config, StyleOptions, ThemeOptions};
+use crate::display::palette;
+use colored::Color;
+use std::fs;
+use std::path::PathBuf;
+
+const BUILTIN: &[(&str, &str)] = &[
+    ("default", include_str!("themes/default.toml")),
+    ("duo", include_str!("themes/duo.toml")),
+    ("mono", include_str!("themes/mono.toml")),
+    ("nord", include_str!("themes/nord.toml")),
+    ("dracula", include_str!("themes/dracula.toml")),
+    ("gruvbox", include_str!("themes/gruvbox.toml")),
+];
+
+// Themes may name a theme to build on, this far
+const MAX_DEPTH: usize = 8;
+
+const BAR_SYMBOL: &str = "   ";
+const BAR_PALETTE: [Color; 8] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub dim: bool,
+}
+
+impl Style {
+    pub fn fg(color: Color) -> Self {
+        Self {
+            color: Some(color),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub label: Style,
+    pub separator: Style,
+    pub value: Style,
+    pub title: Style,
+    pub underline: Style,
+    pub color_bar: Style,
+    pub bar_symbol: String,
+    pub bar_palette: Vec<Color>,
+}
+
+impl Theme {
+    /// Resolves "primary" and "secondary" in `options` to the given colors.
+    pub fn new(options: &ThemeOptions, primary: Color, secondary: Option<Color>) -> Self {
+        let color = |value: &Option<String>| match value.as_deref().map(str::to_lowercase).as_deref() {
+            None | Some("none") | Some("default") => None,
+            Some("primary") => Some(primary),
+            Some("secondary") => secondary,
+            Some(value) => palette::parse(value),
+        };
+        let style = |options: &StyleOptions| Style {
+            color: color(&options.color),
+            background: color(&options.background),
+            bold: options.bold.unwrap_or(false),
+            italic: options.italic.unwrap_or(false),
+            dim: options.dim.unwrap_or(false),
+        };
+
+        let bar_palette: Vec<Color> = options
+            .colors
+            .palette
+            .iter()
+            .flatten()
+            .filter_map(|c| palette::parse(c))
+            .collect();
+
+        Self {
+            label: style(&options.label),
+            separator: style(&options.separator),
+            value: style(&options.value),
+            title: style(&options.title),
+            underline: style(&options.underline),
+            color_bar: style(&options.colors.style),
+            bar_symbol: options.colors.symbol.clone().unwrap_or_else(|| BAR_SYMBOL.to_string()),
+            bar_palette: if bar_palette.is_empty() {
+                BAR_PALETTE.to_vec()
+            } else {
+                bar_palette
+            },
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let options = load(ThemeOptions::default()).unwrap_or_default();
+        Theme::new(&options, Color::Cyan, Some(Color::White))
+    }
+}
+
+pub fn names() -> Vec<&'static str> {
+    BUILTIN.iter().map(|(name, _)| *name).collect()
+}
+
+/// The theme `options` names with the fields of `options` on top, every
+/// color checked.
+pub fn load(options: ThemeOptions) -> Result<ThemeOptions, String> {
+    let name = options.name.clone().unwrap_or_else(|| "default".to_string());
+    let theme = options.over(layers(&name, 0)?);
+    check_colors(&theme)?;
+    Ok(theme)
+}
+
+fn layers(name: &str, depth: usize) -> Result<ThemeOptions, String> {
+    let theme = read(name)?;
+    let base = match theme.name.as_deref() {
+        Some(parent) if parent != name => {
+            if depth >= MAX_DEPTH {
+                return Err(format!("theme '{}' builds on too many themes", name));
+            }
+            layers(parent, depth + 1)?
+        }
+        _ if name != "default" => read("default")?,
+        _ => ThemeOptions::default(),
+    };
+    Ok(ThemeOptions { name: None, ..theme }.over(base))
+}
+
+fn read(name: &str) -> Result<ThemeOptions, String> {
+    let content = match BUILTIN.iter().find(|(builtin, _)| *builtin == name) {
+        Some((_, content)) => content.to_string(),
+        None => {
+            let unknown = || format!("unknown theme '{}', built-in themes are {}", name, names().join(", "));
+            let path = theme_path(name).ok_or_else(unknown)?;
+            fs::read_to_string(&path).map_err(|e| format!("{} ({}: {})", unknown(), path.display(), e))?
+        }
+    };
+    toml::from_str(&content).map_err(|e| format!("Failed to parse theme '{}': {}", name, e))
+}
+
+fn theme_path(name: &str) -> Option<PathBuf> {
+    if name.ends_with(".toml") || name.contains('/') {
+        return Some(PathBuf::from(name));
+    }
+    let dir = Config::config_path()?.parent()?.join("themes");
+    Some(dir.join(format!("{}.toml", name)))
+}
+
+fn check_colors(theme: &ThemeOptions) -> Result<(), String> {
+    let elements = [
+        ("label", &theme.label),
+        ("separator", &theme.separator),
+        ("value", &theme.value),
+        ("title", &theme.title),
+        ("underline", &theme.underline),
+        ("colors", &theme.colors.style),
+    ];
+    let mut colors: Vec<(String, &String)> = Vec::new();
+    for (element, style) in elements {
+        colors.extend(style.color.iter().map(|c| (format!("{}.color", element), c)));
+        colors.extend(style.background.iter().map(|c| (format!("{}.background", element), c)));
+    }
+    colors.extend(theme.colors.palette.iter().flatten().map(|c| ("colors.palette".to_string(), c)));
+
+    for (field, value) in colors {
+        let known = matches!(value.to_lowercase().as_str(), "primary" | "secondary" | "none" | "default");
+        if !known && palette::parse(value).is_none() {
+            return Err(format!("unknown color '{}' for theme {}", value, field));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,385 @@
+// Opt-in log of past fetches for `hyperfetch history`.
+//
+// With `[history] enabled = true`, every full fetch of this machine appends
+// one JSON line with a few key values to `history.jsonl` in the XDG data
+// dir. Lines that fail to parse are skipped, so a torn write or a record
+// from a future version does not hide the rest.
+
+use crate::display::format;
+use crate::info::system::timestamp_to_datetime;
+use crate::info::SystemInfo;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    // Unix time of the fetch
+    pub time: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kernel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uptime_seconds: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_used: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_total: Option<u64>,
+    // Package count per manager
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub packages: BTreeMap<String, u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disks: Vec<DiskUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskUsage {
+    pub mount: String,
+    pub used_bytes: u64,
+    pub size_bytes: u64,
+}
+
+impl Record {
+    pub fn new(info: &SystemInfo) -> Self {
+        Self {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            os: info.os.clone(),
+            kernel: info.kernel.clone(),
+            uptime_seconds: info.uptime_seconds,
+            memory_used: info.memory_used,
+            memory_total: info.memory_total,
+            packages: info
+                .package_counts
+                .iter()
+                .map(|p| (p.manager.clone(), p.count))
+                .collect(),
+            disks: info
+                .disks
+                .iter()
+                .map(|d| DiskUsage {
+                    mount: d.mount.clone(),
+                    used_bytes: d.used_bytes,
+                    size_bytes: d.size_bytes,
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn history_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|p| p.join("hyperfetch").join("history.jsonl"))
+}
+
+/// Appends a record for `info`, dropping the oldest ones beyond `max_records`.
+/// Failures are ignored, history must never break a fetch.
+pub fn record(info: &SystemInfo, max_records: usize) {
+    if let Some(path) = history_path() {
+        record_to(&path, &Record::new(info), max_records);
+    }
+}
+
+/// `record` with another history file.
+pub fn record_to(path: &Path, record: &Record, max_records: usize) {
+    let Ok(line) = serde_json::to_string(record) else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+
+    // Trimming rewrites the whole file, so allow some slack before doing it
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    let count = existing.lines().count() + 1;
+    if count <= max_records + max_records / 10 {
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+            let _ = writeln!(file, "{}", line);
+        }
+        return;
+    }
+
+    let mut lines: Vec<&str> = existing.lines().collect();
+    lines.push(&line);
+    let keep = &lines[lines.len().saturating_sub(max_records)..];
+    let content: String = keep.iter().map(|l| format!("{}\n", l)).collect();
+
+    // Same as the cache: never leave a half-written file behind
+    let tmp = path.with_extension(format!("jsonl.{}", std::process::id()));
+    if fs::write(&tmp, content).is_ok() && fs::rename(&tmp, path).is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+}
+
+/// All stored records, oldest first.
+pub fn load() -> Vec<Record> {
+    history_path().map(|path| load_from(&path)).unwrap_or_default()
+}
+
+/// The records of another history file.
+pub fn load_from(path: &Path) -> Vec<Record> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Recent records, the longest uptime and how kernels, package counts and
+/// disk usage developed.
+pub fn print_summary(records: &[Record], recent: usize) {
+    let (Some(first), Some(last)) = (records.first(), records.last()) else {
+        println!("No history yet. Enable it with `enabled = true` under [history] in the config.");
+        return;
+    };
+
+    println!(
+        "{} records from {} to {}",
+        records.len(),
+        datetime(first.time),
+        datetime(last.time)
+    );
+
+    println!("\n{}", "Recent".bold());
+    for record in &records[records.len().saturating_sub(recent)..] {
+        let mut parts = vec![datetime(record.time)];
+        parts.extend(record.kernel.clone());
+        if let Some(uptime) = record.uptime_seconds {
+            parts.push(format!("up {}", format::uptime(uptime)));
+        }
+        if let (Some(used), Some(total)) = (record.memory_used, record.memory_total) {
+            parts.push(format!("memory {}", format::usage(used, total)));
+        }
+        if !record.packages.is_empty() {
+            parts.push(format!("{} packages", record.packages.values().sum::<u32>()));
+        }
+        println!("  {}", parts.join("  "));
+    }
+
+    if let Some(longest) = records
+        .iter()
+        .filter(|r| r.uptime_seconds.is_some())
+        .max_by_key(|r| r.uptime_seconds)
+    {
+        println!(
+            "\n{} {} (seen {}{})",
+            "Longest uptime:".bold(),
+            format::uptime(longest.uptime_seconds.unwrap_or(0)),
+            datetime(longest.time),
+            longest.kernel.as_ref().map(|k| format!(", kernel {}", k)).unwrap_or_default()
+        );
+    }
+
+    print_kernels(records);
+    print_packages(records);
+    print_disks(records);
+}
+
+// Each kernel with the first and last time it was seen, in order of appearance
+fn print_kernels(records: &[Record]) {
+    let mut kernels: Vec<(&str, u64, u64)> = Vec::new();
+    for record in records {
+        let Some(kernel) = record.kernel.as_deref() else {
+            continue;
+        };
+        match kernels.iter_mut().find(|(k, _, _)| *k == kernel) {
+            Some(entry) => entry.2 = record.time,
+            None => kernels.push((kernel, record.time, record.time)),
+        }
+    }
+    if kernels.is_empty() {
+        return;
+    }
+
+    println!("\n{}", "Kernels".bold());
+    let width = kernels.iter().map(|(k, _, _)| k.chars().count()).max().unwrap_or(0);
+    for (kernel, from, to) in kernels {
+        println!("  {:width$}  {} → {}", kernel, datetime(from), datetime(to), width = width);
+    }
+}
+
+fn print_packages(records: &[Record]) {
+    let lines = package_days(records);
+    if lines.is_empty() {
+        return;
+    }
+    println!("\n{}", "Packages".bold());
+    for line in lines {
+        println!("  {}", line);
+    }
+}
+
+// The counts at the end of the first day, then of every day they changed
+// with the change per manager
+fn package_days(records: &[Record]) -> Vec<String> {
+    let mut days: Vec<(String, &BTreeMap<String, u32>)> = Vec::new();
+    for record in records.iter().filter(|r| !r.packages.is_empty()) {
+        let day = date(record.time);
+        match days.last_mut() {
+            Some(last) if last.0 == day => last.1 = &record.packages,
+            _ => days.push((day, &record.packages)),
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut previous: Option<&BTreeMap<String, u32>> = None;
+    for (day, counts) in days {
+        let total: u32 = counts.values().sum();
+        let detail = match previous {
+            None => counts
+                .iter()
+                .map(|(manager, count)| format!("{} {}", count, manager))
+                .collect::<Vec<_>>(),
+            Some(before) => {
+                let mut managers: Vec<&String> = before.keys().chain(counts.keys()).collect();
+                managers.sort();
+                managers.dedup();
+                managers
+                    .into_iter()
+                    .filter_map(|manager| {
+                        let old = before.get(manager).copied().unwrap_or(0) as i64;
+                        let new = counts.get(manager).copied().unwrap_or(0) as i64;
+                        (old != new).then(|| format!("{} {}", format::signed(new < old, new.abs_diff(old)), manager))
+                    })
+                    .collect()
+            }
+        };
+        if previous.is_some() && detail.is_empty() {
+            continue;
+        }
+        lines.push(format!("{}  {:>6}  {}", day, total, detail.join(", ")));
+        previous = Some(counts);
+    }
+    lines
+}
+
+// Used space per mount between its first and last record, with the daily rate
+fn print_disks(records: &[Record]) {
+    let mut mounts: Vec<(&str, &Record, &DiskUsage, &Record, &DiskUsage)> = Vec::new();
+    for record in records {
+        for disk in &record.disks {
+            match mounts.iter_mut().find(|m| m.0 == disk.mount) {
+                Some(entry) => {
+                    entry.3 = record;
+                    entry.4 = disk;
+                }
+                None => mounts.push((&disk.mount, record, disk, record, disk)),
+            }
+        }
+    }
+    if mounts.is_empty() {
+        return;
+    }
+
+    println!("\n{}", "Disk usage".bold());
+    let width = mounts.iter().map(|m| m.0.chars().count()).max().unwrap_or(0);
+    for (mount, first_record, first, last_record, last) in mounts {
+        let change = last.used_bytes as i64 - first.used_bytes as i64;
+        let days = last_record.time.saturating_sub(first_record.time) as f64 / 86400.0;
+        let rate = if days >= 1.0 {
+            let per_day = (change as f64 / days) as i64;
+            format!(", {}/day", format::signed(per_day < 0, format::bytes(per_day.unsigned_abs())))
+        } else {
+            String::new()
+        };
+        println!(
+            "  {:width$}  {} → {} of {} ({}{})",
+            mount,
+            format::bytes(first.used_bytes),
+            format::bytes(last.used_bytes),
+            format::bytes(last.size_bytes),
+            format::signed(change < 0, format::bytes(change.unsigned_abs())),
+            rate,
+            width = width
+        );
+    }
+}
+
+fn date(time: u64) -> String {
+    let (year, month, day, _, _) = timestamp_to_datetime(time as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn datetime(time: u64) -> String {
+    let (year, month, day, hour, min) = timestamp_to_datetime(time as i64);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, hour, min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86400;
+    // 2024-02-01 12:00 UTC
+    const NOON: u64 = 1_706_788_800;
+
+    fn scratch(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("hyperfetch-history-{}-{}.jsonl", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn record(time: u64, kernel: &str, packages: &[(&str, u32)]) -> Record {
+        Record {
+            time,
+            kernel: Some(kernel.to_string()),
+            packages: packages.iter().map(|(m, c)| (m.to_string(), *c)).collect(),
+            ..Record::new(&SystemInfo::default())
+        }
+    }
+
+    #[test]
+    fn records_are_trimmed_to_the_newest() {
+        let path = scratch("trim");
+        for i in 0..25 {
+            record_to(&path, &record(NOON + i, &format!("k{}", i), &[]), 10);
+            let count = load_from(&path).len();
+            assert!(count <= 11, "{} records after {}", count, i);
+        }
+        let kernels: Vec<String> = load_from(&path).into_iter().filter_map(|r| r.kernel).collect();
+        assert_eq!(kernels.last().map(String::as_str), Some("k24"));
+        assert_eq!(kernels.first(), Some(&format!("k{}", 25 - kernels.len())));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        let path = scratch("malformed");
+        let good = serde_json::to_string(&record(NOON, "6.1.0", &[])).unwrap();
+        let content = format!(
+            "{good}\nnot json\n{{\"time\": 5, \"kernel\": \n{{\"kernel\": \"no time\"}}\n\n{{\"time\": 7, \"future\": true}}\n{good}\n"
+        );
+        fs::write(&path, content).unwrap();
+
+        let times: Vec<u64> = load_from(&path).iter().map(|r| r.time).collect();
+        assert_eq!(times, [NOON, 7, NOON]);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn packages_are_shown_per_day() {
+        let records = [
+            record(NOON, "6.1", &[("apt", 1200), ("flatpak", 10)]),
+            // Only the last record of a day counts
+            record(NOON + 60, "6.1", &[("apt", 1201), ("flatpak", 10)]),
+            record(NOON + DAY, "6.1", &[("apt", 1201), ("flatpak", 10)]),
+            record(NOON + 2 * DAY, "6.1", &[]),
+            record(NOON + 3 * DAY, "6.1", &[("apt", 1199), ("pip", 3)]),
+        ];
+        assert_eq!(
+            package_days(&records),
+            [
+                "2024-02-01    1211  1201 apt, 10 flatpak",
+                "2024-02-04    1202  −2 apt, −10 flatpak, +3 pip",
+            ]
+        );
+    }
+}
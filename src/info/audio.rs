@@ -1,15 +1,19 @@
-use crate::info::SystemInfo;
-use std::process::Command;
+use crate::config::InfoOptions;
+use crate::info::{command, profile, SystemInfo};
 
-pub fn gather(info: &mut SystemInfo) {
-    gather_audio_device(info);
-    gather_volume(info);
+pub fn gather(info: &mut SystemInfo, options: &InfoOptions) {
+    if options.audio_device {
+        profile::time("gather_audio_device", || gather_audio_device(info));
+    }
+    if options.volume {
+        profile::time("gather_volume", || gather_volume(info));
+    }
 }
 
 fn gather_audio_device(info: &mut SystemInfo) {
     // Try PipeWire first
-    if let Ok(output) = Command::new("wpctl").args(["status"]).output() {
-        if output.status.success() {
+    if let Some(output) = command::run("wpctl", &["status"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             // Look for the default sink
             let mut in_sinks = false;
@@ -19,16 +23,19 @@ fn gather_audio_device(info: &mut SystemInfo) {
                     continue;
                 }
                 if in_sinks {
-                    if line.trim().starts_with('*') {
+                    // Lines are indented with tree drawing characters
+                    let entry = line.trim_start_matches([' ', '│', '├', '└', '─']);
+                    if entry.starts_with('*') {
                         // This is the default sink
-                        // Format: " *  123. Device Name [vol: 1.00]"
-                        if let Some(name) = line.split('.').nth(1) {
+                        // Format: " │  *   123. Device Name [vol: 1.00]"
+                        if let Some((_, name)) = entry.split_once('.') {
                             let name = name.split('[').next().unwrap_or(name).trim();
                             info.audio_device = Some(format!("{} (PipeWire)", name));
                             return;
                         }
                     }
-                    if !line.starts_with(' ') && !line.is_empty() {
+                    // Next section (" ├─ Sources:") or next top-level group
+                    if entry.ends_with(':') || (!line.starts_with(' ') && !line.is_empty()) {
                         break;
                     }
                 }
@@ -37,19 +44,15 @@ fn gather_audio_device(info: &mut SystemInfo) {
     }
 
     // Try PulseAudio
-    if let Ok(output) = Command::new("pactl")
-        .args(["get-default-sink"])
-        .output()
+    if let Some(output) = command::run("pactl", &["get-default-sink"])
     {
-        if output.status.success() {
+        if output.success() {
             let sink_name = String::from_utf8_lossy(&output.stdout).trim().to_string();
             if !sink_name.is_empty() {
                 // Get more info about the sink
-                if let Ok(info_output) = Command::new("pactl")
-                    .args(["list", "sinks"])
-                    .output()
+                if let Some(info_output) = command::run("pactl", &["list", "sinks"])
                 {
-                    if info_output.status.success() {
+                    if info_output.success() {
                         let content = String::from_utf8_lossy(&info_output.stdout);
                         let mut in_default = false;
 
@@ -73,8 +76,8 @@ fn gather_audio_device(info: &mut SystemInfo) {
     }
 
     // Try ALSA
-    if let Ok(output) = Command::new("aplay").args(["-l"]).output() {
-        if output.status.success() {
+    if let Some(output) = command::run("aplay", &["-l"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             for line in content.lines() {
                 if line.starts_with("card") {
@@ -94,24 +97,18 @@ fn gather_audio_device(info: &mut SystemInfo) {
 
 fn gather_volume(info: &mut SystemInfo) {
     // Try PipeWire/WirePlumber first
-    if let Ok(output) = Command::new("wpctl")
-        .args(["get-volume", "@DEFAULT_AUDIO_SINK@"])
-        .output()
+    if let Some(output) = command::run("wpctl", &["get-volume", "@DEFAULT_AUDIO_SINK@"])
     {
-        if output.status.success() {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             // Format: "Volume: 0.74" or "Volume: 0.74 [MUTED]"
             if let Some(vol_str) = content.split(':').nth(1) {
                 let parts: Vec<&str> = vol_str.split_whitespace().collect();
                 if let Some(vol) = parts.first() {
                     if let Ok(vol_float) = vol.parse::<f32>() {
-                        let percent = (vol_float * 100.0) as u32;
-                        let muted = content.contains("[MUTED]");
-                        if muted {
-                            info.volume = Some(format!("{}% (Muted)", percent));
-                        } else {
-                            info.volume = Some(format!("{}%", percent));
-                        }
+                        let percent = (vol_float * 100.0).round() as u32;
+                        info.volume_percent = Some(percent);
+                        info.volume_muted = Some(content.contains("[MUTED]"));
                         return;
                     }
                 }
@@ -120,11 +117,9 @@ fn gather_volume(info: &mut SystemInfo) {
     }
 
     // Try PulseAudio
-    if let Ok(output) = Command::new("pactl")
-        .args(["get-sink-volume", "@DEFAULT_SINK@"])
-        .output()
+    if let Some(output) = command::run("pactl", &["get-sink-volume", "@DEFAULT_SINK@"])
     {
-        if output.status.success() {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             // Format: "Volume: front-left: 48000 /  73% / -8.11 dB, ..."
             for part in content.split('/') {
@@ -133,18 +128,12 @@ fn gather_volume(info: &mut SystemInfo) {
                     let volume = trimmed.trim_end_matches('%').trim();
                     if let Ok(vol) = volume.parse::<u32>() {
                         // Check if muted
-                        let muted = Command::new("pactl")
-                            .args(["get-sink-mute", "@DEFAULT_SINK@"])
-                            .output()
-                            .ok()
+                        let muted = command::run("pactl", &["get-sink-mute", "@DEFAULT_SINK@"])
                             .map(|o| String::from_utf8_lossy(&o.stdout).contains("yes"))
                             .unwrap_or(false);
 
-                        if muted {
-                            info.volume = Some(format!("{}% (Muted)", vol));
-                        } else {
-                            info.volume = Some(format!("{}%", vol));
-                        }
+                        info.volume_percent = Some(vol);
+                        info.volume_muted = Some(muted);
                         return;
                     }
                 }
@@ -153,11 +142,9 @@ fn gather_volume(info: &mut SystemInfo) {
     }
 
     // Try ALSA with amixer
-    if let Ok(output) = Command::new("amixer")
-        .args(["get", "Master"])
-        .output()
+    if let Some(output) = command::run("amixer", &["get", "Master"])
     {
-        if output.status.success() {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             for line in content.lines() {
                 if line.contains('[') && line.contains('%') {
@@ -166,12 +153,8 @@ fn gather_volume(info: &mut SystemInfo) {
                         if let Some(end) = line[start..].find('%') {
                             let vol_str = &line[start + 1..start + end];
                             if let Ok(vol) = vol_str.parse::<u32>() {
-                                let muted = line.contains("[off]");
-                                if muted {
-                                    info.volume = Some(format!("{}% (Muted)", vol));
-                                } else {
-                                    info.volume = Some(format!("{}%", vol));
-                                }
+                                info.volume_percent = Some(vol);
+                                info.volume_muted = Some(line.contains("[off]"));
                                 return;
                             }
                         }
@@ -0,0 +1,255 @@
+// Persistent cache for slow probes that rarely change (package counts, GPU
+// names, board strings, terminal fonts).
+//
+// Entries live in one JSON file under the XDG cache dir. An entry is reused
+// while it is younger than its TTL, was stored during the current boot and
+// the mtimes of its source files are unchanged. Values computed while an external command overran are not
+// stored, so a timeout does not stick around for the whole TTL.
+
+use crate::config::CacheTtl;
+use crate::info::command;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Reuse valid entries and store new ones
+    Normal,
+    /// Ignore stored entries but store the fresh values
+    Refresh,
+    /// Neither read nor write the cache
+    Off,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    value: Value,
+    stored: u64,
+    // Source path and its mtime (None when missing) at the time of storing
+    sources: Vec<(PathBuf, Option<u64>)>,
+    // Hardware can only change across a reboot, which sysfs mtimes do not show
+    #[serde(default)]
+    boot: Option<String>,
+}
+
+struct State {
+    mode: Mode,
+    ttl: Option<CacheTtl>,
+    entries: Option<HashMap<String, Entry>>,
+    dirty: bool,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    mode: Mode::Off,
+    ttl: None,
+    entries: None,
+    dirty: false,
+});
+
+pub fn configure(mode: Mode, ttl: CacheTtl) {
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    state.mode = mode;
+    state.ttl = Some(ttl);
+}
+
+// Keys are "<field>" or "<field>.<detail>", the TTL comes from the field
+fn ttl_for(ttl: &CacheTtl, key: &str) -> Duration {
+    let secs = match key.split('.').next().unwrap_or(key) {
+        "packages" => ttl.packages,
+        "gpu" => ttl.gpu,
+        "motherboard" => ttl.motherboard,
+        "bios" => ttl.bios,
+        "terminal_font" => ttl.terminal_font,
+        _ => 0,
+    };
+    Duration::from_secs(secs)
+}
+
+fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|p| p.join("hyperfetch").join("cache.json"))
+}
+
+fn load() -> HashMap<String, Entry> {
+    cache_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Nanoseconds, so changes within the same second are still noticed
+fn mtime(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    modified.duration_since(UNIX_EPOCH).ok().map(|d| d.as_nanos() as u64)
+}
+
+// None where the kernel has no boot id, entries then outlive a reboot
+fn boot_id() -> Option<String> {
+    static BOOT_ID: OnceLock<Option<String>> = OnceLock::new();
+    BOOT_ID
+        .get_or_init(|| {
+            fs::read_to_string("/proc/sys/kernel/random/boot_id")
+                .ok()
+                .map(|id| id.trim().to_string())
+        })
+        .clone()
+}
+
+fn is_valid(entry: &Entry, ttl: Duration) -> bool {
+    now().saturating_sub(entry.stored) < ttl.as_secs()
+        && entry.boot == boot_id()
+        && entry
+            .sources
+            .iter()
+            .all(|(path, stored)| mtime(path) == *stored)
+}
+
+/// Returns the cached value for `key`, or computes and stores it. A zero
+/// TTL disables caching for the key's field.
+pub fn cached<T, F>(key: &str, sources: &[PathBuf], compute: F) -> T
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> T,
+{
+    let (mode, ttl) = {
+        let state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        let ttl = state.ttl.as_ref().map(|t| ttl_for(t, key)).unwrap_or_default();
+        (state.mode, ttl)
+    };
+    if mode == Mode::Off || ttl.is_zero() {
+        return compute();
+    }
+
+    if mode == Mode::Normal {
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        let entries = state.entries.get_or_insert_with(load);
+        if let Some(entry) = entries.get(key).filter(|e| is_valid(e, ttl)) {
+            if let Ok(value) = serde_json::from_value(entry.value.clone()) {
+                return value;
+            }
+        }
+    }
+
+    // Source mtimes are taken first so a change during the probe invalidates
+    // the entry. The probe runs without the lock, others run in parallel.
+    let sources = sources.iter().map(|p| (p.clone(), mtime(p))).collect();
+    let overruns = command::overruns();
+    let value = compute();
+    if command::overruns() != overruns {
+        return value;
+    }
+
+    if let Ok(json) = serde_json::to_value(&value) {
+        let entry = Entry {
+            value: json,
+            stored: now(),
+            sources,
+            boot: boot_id(),
+        };
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .entries
+            .get_or_insert_with(load)
+            .insert(key.to_string(), entry);
+        state.dirty = true;
+    }
+
+    value
+}
+
+/// Writes the cache file if anything changed since it was loaded.
+pub fn save() {
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    if !state.dirty {
+        return;
+    }
+    state.dirty = false;
+
+    let (Some(path), Some(entries)) = (cache_path(), state.entries.as_ref()) else {
+        return;
+    };
+    let Ok(content) = serde_json::to_string(entries) else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+
+    // Write to a temporary file first so a concurrent run never reads half a cache
+    let tmp = path.with_extension(format!("json.{}", std::process::id()));
+    if fs::write(&tmp, content).is_ok() && fs::rename(&tmp, &path).is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn entry(stored: u64, sources: &[PathBuf]) -> Entry {
+        Entry {
+            value: Value::from(42),
+            stored,
+            sources: sources.iter().map(|p| (p.clone(), mtime(p))).collect(),
+            boot: boot_id(),
+        }
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hyperfetch-cache-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn entries_expire_with_their_ttl() {
+        assert!(is_valid(&entry(now(), &[]), HOUR));
+        assert!(is_valid(&entry(now() - 3500, &[]), HOUR));
+        assert!(!is_valid(&entry(now() - 3600, &[]), HOUR));
+        assert!(!is_valid(&entry(now(), &[]), Duration::ZERO));
+    }
+
+    #[test]
+    fn entries_follow_their_sources() {
+        let dir = scratch("sources");
+        let source = dir.join("status");
+        fs::write(&source, "installed").unwrap();
+        let missing = dir.join("missing");
+
+        let stored = entry(now(), &[source.clone(), missing.clone()]);
+        assert!(is_valid(&stored, HOUR));
+
+        let file = File::options().write(true).open(&source).unwrap();
+        file.set_modified(SystemTime::now() - HOUR).unwrap();
+        assert!(!is_valid(&stored, HOUR));
+
+        // A source that appears counts as a change too
+        let stored = entry(now(), std::slice::from_ref(&missing));
+        fs::write(&missing, "").unwrap();
+        assert!(!is_valid(&stored, HOUR));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn entries_from_another_boot_are_stale() {
+        let mut stored = entry(now(), &[]);
+        stored.boot = Some("00000000-0000-0000-0000-000000000000".to_string());
+        assert!(!is_valid(&stored, HOUR));
+    }
+}
@@ -0,0 +1,230 @@
+// Runs the external programs used by the info modules.
+//
+// Every command gets a timeout, and all commands in one fetch share a time
+// budget. A command that overruns is killed together with its children and
+// reported as missing, so the field it would have filled stays empty.
+//
+// The actual execution goes through a `CommandRunner`. `SystemRunner`
+// spawns real processes; tests install their own runner with canned output.
+
+use crate::info::profile::{self, Kind};
+use crate::info::sysroot;
+use std::cell::Cell;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default)]
+pub struct CommandOutput {
+    pub code: Option<i32>,
+    pub stdout: Vec<u8>,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+/// The command ran past its timeout and was killed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut;
+
+pub trait CommandRunner: Send + Sync {
+    /// Runs `program` with `args` for at most `timeout`. `Ok(None)` means
+    /// the program could not be started, for example because it is missing.
+    fn run(&self, program: &str, args: &[&str], timeout: Duration) -> Result<Option<CommandOutput>, TimedOut>;
+}
+
+/// Spawns real processes.
+pub struct SystemRunner;
+
+static RUNNER: RwLock<Option<Arc<dyn CommandRunner>>> = RwLock::new(None);
+
+/// Replaces the runner used for all commands.
+// Only used through the library (tests), the binary compiles its own copy
+#[allow(dead_code)]
+pub fn set_runner(runner: Arc<dyn CommandRunner>) {
+    *RUNNER.write().unwrap_or_else(|e| e.into_inner()) = Some(runner);
+}
+
+/// Goes back to spawning real processes.
+#[allow(dead_code)]
+pub fn reset_runner() {
+    *RUNNER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+struct Limits {
+    timeout: Duration,
+    budget: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+static LIMITS: Mutex<Limits> = Mutex::new(Limits {
+    timeout: Duration::from_millis(2000),
+    budget: Some(Duration::from_millis(5000)),
+    deadline: None,
+});
+
+thread_local! {
+    static OVERRUNS: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Number of commands on this thread that timed out or were skipped for
+/// lack of budget. Lets callers tell an empty result from a cut-off one.
+pub fn overruns() -> u32 {
+    OVERRUNS.with(Cell::get)
+}
+
+/// Sets the per-command timeout and the total budget. A zero budget means
+/// commands are only limited by their own timeout.
+pub fn configure(timeout: Duration, budget: Duration) {
+    let mut limits = LIMITS.lock().unwrap_or_else(|e| e.into_inner());
+    limits.timeout = timeout;
+    limits.budget = (!budget.is_zero()).then_some(budget);
+}
+
+/// Starts the budget clock. Called at the beginning of every fetch.
+pub fn start_budget() {
+    let mut limits = LIMITS.lock().unwrap_or_else(|e| e.into_inner());
+    limits.deadline = limits.budget.map(|budget| Instant::now() + budget);
+}
+
+// Time the next command may take, or None once the budget is spent
+fn time_allowed() -> Option<Duration> {
+    let limits = LIMITS.lock().unwrap_or_else(|e| e.into_inner());
+    match limits.deadline {
+        Some(deadline) => {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                None
+            } else {
+                Some(remaining.min(limits.timeout))
+            }
+        }
+        None => Some(limits.timeout),
+    }
+}
+
+/// Runs `program` with `args` and returns its output, or None if it could
+/// not be started or did not finish in time.
+pub fn run(program: &str, args: &[&str]) -> Option<CommandOutput> {
+    let start = Instant::now();
+    let output = match time_allowed() {
+        Some(timeout) => {
+            let runner = RUNNER.read().unwrap_or_else(|e| e.into_inner()).clone();
+            match runner {
+                Some(runner) => runner.run(program, args, timeout),
+                None => SystemRunner.run(program, args, timeout),
+            }
+        }
+        None => Err(TimedOut),
+    };
+    if output.is_err() {
+        OVERRUNS.with(|count| count.set(count.get() + 1));
+    }
+
+    if profile::enabled() {
+        let name = std::iter::once(program).chain(args.iter().copied()).collect::<Vec<_>>().join(" ");
+        let timed_out = output.is_err();
+        profile::record(&name, Kind::Command, start.elapsed(), timed_out);
+    }
+
+    output.ok().flatten()
+}
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[&str], timeout: Duration) -> Result<Option<CommandOutput>, TimedOut> {
+        // Host programs know nothing about an alternate root, their output
+        // would mix the live system into the result
+        if sysroot::is_set() {
+            return Ok(None);
+        }
+        spawn_with_timeout(program, args, timeout)
+    }
+}
+
+fn spawn_with_timeout(program: &str, args: &[&str], timeout: Duration) -> Result<Option<CommandOutput>, TimedOut> {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+
+    // Own process group, so the whole tree can be killed on timeout
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let deadline = Instant::now() + timeout;
+    let Ok(mut child) = command.spawn() else {
+        return Ok(None);
+    };
+
+    // Drain stdout while waiting, a full pipe would block the child
+    let (sender, stdout) = mpsc::channel();
+    if let Some(pipe) = child.stdout.take() {
+        thread::spawn(move || sender.send(drain(pipe)));
+    }
+
+    let Some(code) = wait_with_timeout(&mut child, timeout) else {
+        kill(&mut child);
+        return Err(TimedOut);
+    };
+
+    // A background process the command left behind can hold stdout open
+    // after the command itself exited
+    let stdout = match stdout.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(stdout) => stdout,
+        Err(mpsc::RecvTimeoutError::Disconnected) => Vec::new(),
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            kill(&mut child);
+            return Err(TimedOut);
+        }
+    };
+    Ok(Some(CommandOutput { code, stdout }))
+}
+
+fn drain(mut pipe: impl Read) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    let _ = pipe.read_to_end(&mut buffer);
+    buffer
+}
+
+// Outer None means the command timed out, inner None that it was killed by a signal
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Option<Option<i32>> {
+    let start = Instant::now();
+    let mut interval = Duration::from_millis(1);
+
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Some(status.code()),
+            Ok(None) => {}
+            Err(_) => return None,
+        }
+
+        let elapsed = start.elapsed();
+        if elapsed >= timeout {
+            return None;
+        }
+
+        thread::sleep(interval.min(timeout - elapsed));
+        interval = (interval * 2).min(Duration::from_millis(50));
+    }
+}
+
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        // Negative pid signals the process group
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
@@ -1,17 +1,28 @@
-use crate::info::SystemInfo;
+use crate::config::InfoOptions;
+use crate::info::{cache, command, profile, sysroot, SystemInfo};
 use std::env;
-use std::fs;
-use std::path::Path;
-use std::process::Command;
-
-pub fn gather(info: &mut SystemInfo) {
-    gather_display_server(info);
-    gather_resolution(info);
-    gather_de(info);
-    gather_wm(info);
-    gather_themes(info);
-    gather_terminal(info);
-    gather_shell(info);
+use std::path::{Path, PathBuf};
+
+pub fn gather(info: &mut SystemInfo, options: &InfoOptions) {
+    if options.display_server {
+        profile::time("gather_display_server", || gather_display_server(info));
+    }
+    if options.resolution {
+        profile::time("gather_resolution", || gather_resolution(info));
+    }
+    if options.de {
+        profile::time("gather_de", || gather_de(info));
+    }
+    if options.wm {
+        profile::time("gather_wm", || gather_wm(info, options));
+    }
+    gather_themes(info, options);
+    if options.terminal {
+        profile::time("gather_terminal", || gather_terminal(info, options));
+    }
+    if options.shell {
+        profile::time("gather_shell", || gather_shell(info, options));
+    }
 }
 
 fn gather_display_server(info: &mut SystemInfo) {
@@ -29,15 +40,15 @@ fn gather_resolution(info: &mut SystemInfo) {
     let mut resolutions = Vec::new();
 
     // Try xrandr first
-    if let Ok(output) = Command::new("xrandr").arg("--current").output() {
-        if output.status.success() {
+    if let Some(output) = command::run("xrandr", &["--current"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
 
             for line in content.lines() {
                 if line.contains(" connected") {
                     // Look for resolution in format like "1920x1080+0+0"
                     for part in line.split_whitespace() {
-                        if part.contains('x') && part.chars().next().map_or(false, |c| c.is_ascii_digit()) {
+                        if part.contains('x') && part.chars().next().is_some_and(|c| c.is_ascii_digit()) {
                             let res = part.split('+').next().unwrap_or(part);
                             push_resolution(&mut resolutions, res);
                         }
@@ -53,14 +64,14 @@ fn gather_resolution(info: &mut SystemInfo) {
     }
 
     // Try wlr-randr for Wayland
-    if let Ok(output) = Command::new("wlr-randr").output() {
-        if output.status.success() {
+    if let Some(output) = command::run("wlr-randr", &[]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
 
             for line in content.lines() {
                 if line.contains("current") {
                     for part in line.split_whitespace() {
-                        if part.contains('x') && part.chars().next().map_or(false, |c| c.is_ascii_digit()) {
+                        if part.contains('x') && part.chars().next().is_some_and(|c| c.is_ascii_digit()) {
                             push_resolution(&mut resolutions, part);
                         }
                     }
@@ -75,7 +86,7 @@ fn gather_resolution(info: &mut SystemInfo) {
     }
 
     // Try reading from /sys for framebuffer resolution
-    if let Ok(content) = fs::read_to_string("/sys/class/graphics/fb0/virtual_size") {
+    if let Ok(content) = sysroot::read_to_string("/sys/class/graphics/fb0/virtual_size") {
         let res = content.trim().replace(',', "x");
         info.resolution = Some(res);
     }
@@ -115,7 +126,7 @@ fn gather_de(info: &mut SystemInfo) {
     }
 }
 
-fn gather_wm(info: &mut SystemInfo) {
+fn gather_wm(info: &mut SystemInfo, options: &InfoOptions) {
     // Check for common WM environment variables
     if let Ok(wm) = env::var("WAYLAND_WM") {
         info.wm = Some(wm);
@@ -152,15 +163,17 @@ fn gather_wm(info: &mut SystemInfo) {
         ("2bwm", "2bwm"),
     ];
 
-    if let Ok(output) = Command::new("ps").args(["-e", "-o", "comm="]).output() {
-        if output.status.success() {
+    if let Some(output) = command::run("ps", &["-e", "-o", "comm="]) {
+        if output.success() {
             let procs = String::from_utf8_lossy(&output.stdout).to_lowercase();
             for (proc_name, wm_name) in wm_list {
                 if procs.lines().any(|l| l.trim() == proc_name) {
                     info.wm = Some(wm_name.to_string());
 
                     // Get WM theme
-                    gather_wm_theme(info, proc_name);
+                    if options.wm_theme {
+                        profile::time("gather_wm_theme", || gather_wm_theme(info, proc_name));
+                    }
                     return;
                 }
             }
@@ -168,8 +181,8 @@ fn gather_wm(info: &mut SystemInfo) {
     }
 
     // Fallback: use wmctrl
-    if let Ok(output) = Command::new("wmctrl").arg("-m").output() {
-        if output.status.success() {
+    if let Some(output) = command::run("wmctrl", &["-m"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             for line in content.lines() {
                 if let Some(name) = line.strip_prefix("Name:") {
@@ -194,7 +207,7 @@ fn gather_wm_theme(info: &mut SystemInfo, wm_name: &str) {
             ];
 
             for path in config_paths.into_iter().flatten() {
-                if let Ok(content) = fs::read_to_string(&path) {
+                if let Ok(content) = sysroot::read_to_string(&path) {
                     if let Some(start) = content.find("<name>") {
                         if let Some(end) = content[start..].find("</name>") {
                             let theme = &content[start + 6..start + end];
@@ -209,15 +222,21 @@ fn gather_wm_theme(info: &mut SystemInfo, wm_name: &str) {
     }
 }
 
-fn gather_themes(info: &mut SystemInfo) {
+fn gather_themes(info: &mut SystemInfo, options: &InfoOptions) {
     // GTK Theme
-    gather_gtk_theme(info);
+    if options.theme {
+        profile::time("gather_gtk_theme", || gather_gtk_theme(info));
+    }
 
     // Icon Theme
-    gather_icon_theme(info);
+    if options.icons {
+        profile::time("gather_icon_theme", || gather_icon_theme(info));
+    }
 
     // Cursor Theme
-    gather_cursor_theme(info);
+    if options.cursor {
+        profile::time("gather_cursor_theme", || gather_cursor_theme(info));
+    }
 }
 
 fn gather_gtk_theme(info: &mut SystemInfo) {
@@ -284,34 +303,37 @@ fn gather_cursor_theme(info: &mut SystemInfo) {
     }
 }
 
-fn gather_terminal(info: &mut SystemInfo) {
+fn gather_terminal(info: &mut SystemInfo, options: &InfoOptions) {
+    if let Some(term) = detect_terminal() {
+        info.terminal = Some(format_terminal_name(&term));
+        if options.terminal_font {
+            profile::time("gather_terminal_font", || gather_terminal_font(info, &term));
+        }
+    }
+}
+
+fn detect_terminal() -> Option<String> {
     // Check TERM_PROGRAM first
     if let Ok(term) = env::var("TERM_PROGRAM") {
-        info.terminal = Some(format_terminal_name(&term));
-        gather_terminal_font(info, &term);
-        return;
+        return Some(term);
     }
 
     // Try to detect from parent process
-    if let Ok(ppid) = fs::read_to_string("/proc/self/stat") {
+    if let Ok(ppid) = sysroot::read_to_string("/proc/self/stat") {
         let parts: Vec<&str> = ppid.split_whitespace().collect();
         if let Some(parent_pid) = parts.get(3) {
-            if let Ok(parent_comm) = fs::read_to_string(format!("/proc/{}/comm", parent_pid)) {
+            if let Ok(parent_comm) = sysroot::read_to_string(format!("/proc/{}/comm", parent_pid)) {
                 let term = parent_comm.trim().to_lowercase();
 
                 // Walk up the process tree to find the terminal
                 let terminal = find_terminal_in_parents(parent_pid.parse().unwrap_or(1));
-                if let Some(t) = terminal {
-                    info.terminal = Some(format_terminal_name(&t));
-                    gather_terminal_font(info, &t);
-                    return;
+                if terminal.is_some() {
+                    return terminal;
                 }
 
                 // Check if the parent is the terminal
                 if is_terminal(&term) {
-                    info.terminal = Some(format_terminal_name(&term));
-                    gather_terminal_font(info, &term);
-                    return;
+                    return Some(term);
                 }
             }
         }
@@ -322,12 +344,12 @@ fn gather_terminal(info: &mut SystemInfo) {
     for var in terminal_vars {
         if let Ok(term) = env::var(var) {
             if !term.is_empty() && term != "dumb" && term != "linux" {
-                info.terminal = Some(format_terminal_name(&term));
-                gather_terminal_font(info, &term);
-                return;
+                return Some(term);
             }
         }
     }
+
+    None
 }
 
 fn find_terminal_in_parents(start_pid: u32) -> Option<String> {
@@ -339,7 +361,7 @@ fn find_terminal_in_parents(start_pid: u32) -> Option<String> {
             break;
         }
 
-        if let Ok(comm) = fs::read_to_string(format!("/proc/{}/comm", pid)) {
+        if let Ok(comm) = sysroot::read_to_string(format!("/proc/{}/comm", pid)) {
             let name = comm.trim().to_lowercase();
             if is_terminal(&name) {
                 return Some(name);
@@ -347,7 +369,7 @@ fn find_terminal_in_parents(start_pid: u32) -> Option<String> {
         }
 
         // Get parent PID
-        if let Ok(stat) = fs::read_to_string(format!("/proc/{}/stat", pid)) {
+        if let Ok(stat) = sysroot::read_to_string(format!("/proc/{}/stat", pid)) {
             let parts: Vec<&str> = stat.split_whitespace().collect();
             if let Some(ppid_str) = parts.get(3) {
                 pid = ppid_str.parse().unwrap_or(0);
@@ -431,44 +453,42 @@ fn format_terminal_name(name: &str) -> String {
 
 fn gather_terminal_font(info: &mut SystemInfo, terminal: &str) {
     let term_lower = terminal.to_lowercase();
+    let configs = terminal_configs(&term_lower);
+    if configs.is_empty() {
+        return;
+    }
 
-    if let Some(config_dir) = dirs::config_dir() {
-        let font = match term_lower.as_str() {
-            "alacritty" => {
-                let config = config_dir.join("alacritty/alacritty.toml");
-                if let Ok(content) = fs::read_to_string(&config) {
-                    extract_alacritty_font(&content)
-                } else {
-                    let config = config_dir.join("alacritty/alacritty.yml");
-                    fs::read_to_string(&config)
-                        .ok()
-                        .and_then(|c| extract_yaml_font(&c, "family"))
-                }
-            }
-            "kitty" => {
-                let config = config_dir.join("kitty/kitty.conf");
-                fs::read_to_string(&config)
-                    .ok()
-                    .and_then(|c| extract_conf_value(&c, "font_family"))
-            }
-            "wezterm" | "wezterm-gui" => {
-                let config = config_dir.join("wezterm/wezterm.lua");
-                fs::read_to_string(&config)
-                    .ok()
-                    .and_then(|c| extract_lua_font(&c))
-            }
-            "foot" => {
-                let config = config_dir.join("foot/foot.ini");
-                fs::read_to_string(&config)
-                    .ok()
-                    .and_then(|c| extract_ini_value(&c, "font"))
-            }
-            _ => None,
-        };
+    // The config files double as cache sources, editing them refreshes the font
+    let key = format!("terminal_font.{}", term_lower);
+    info.terminal_font = cache::cached(&key, &configs, || read_terminal_font(&term_lower, &configs));
+}
 
-        if let Some(f) = font {
-            info.terminal_font = Some(f);
-        }
+fn terminal_configs(term_lower: &str) -> Vec<PathBuf> {
+    let Some(config_dir) = dirs::config_dir() else {
+        return Vec::new();
+    };
+    let files: &[&str] = match term_lower {
+        "alacritty" => &["alacritty/alacritty.toml", "alacritty/alacritty.yml"],
+        "kitty" => &["kitty/kitty.conf"],
+        "wezterm" | "wezterm-gui" => &["wezterm/wezterm.lua"],
+        "foot" => &["foot/foot.ini"],
+        _ => &[],
+    };
+    files.iter().map(|file| config_dir.join(file)).collect()
+}
+
+fn read_terminal_font(term_lower: &str, configs: &[PathBuf]) -> Option<String> {
+    let read = |index: usize| configs.get(index).and_then(|path| sysroot::read_to_string(path).ok());
+
+    match term_lower {
+        "alacritty" => match read(0) {
+            Some(content) => extract_alacritty_font(&content),
+            None => read(1).and_then(|c| extract_yaml_font(&c, "family")),
+        },
+        "kitty" => read(0).and_then(|c| extract_conf_value(&c, "font_family")),
+        "wezterm" | "wezterm-gui" => read(0).and_then(|c| extract_lua_font(&c)),
+        "foot" => read(0).and_then(|c| extract_ini_value(&c, "font")),
+        _ => None,
     }
 }
 
@@ -538,7 +558,7 @@ fn extract_ini_value(content: &str, key: &str) -> Option<String> {
 
 fn read_settings_value(key: &str, paths: &[Option<std::path::PathBuf>]) -> Option<String> {
     for path in paths.iter().flatten() {
-        if let Ok(content) = fs::read_to_string(path) {
+        if let Ok(content) = sysroot::read_to_string(path) {
             for line in content.lines() {
                 if let Some(value) = line.strip_prefix(&format!("{key}=")) {
                     let trimmed = value.trim().trim_matches('\'');
@@ -553,11 +573,8 @@ fn read_settings_value(key: &str, paths: &[Option<std::path::PathBuf>]) -> Optio
 }
 
 fn gsettings_get(schema: &str, key: &str) -> Option<String> {
-    let output = Command::new("gsettings")
-        .args(["get", schema, key])
-        .output()
-        .ok()?;
-    if !output.status.success() {
+    let output = command::run("gsettings", &["get", schema, key])?;
+    if !output.success() {
         return None;
     }
     let value = String::from_utf8_lossy(&output.stdout)
@@ -571,7 +588,7 @@ fn gsettings_get(schema: &str, key: &str) -> Option<String> {
     }
 }
 
-fn gather_shell(info: &mut SystemInfo) {
+fn gather_shell(info: &mut SystemInfo, options: &InfoOptions) {
     // Get shell from environment
     if let Ok(shell_path) = env::var("SHELL") {
         let shell_name = Path::new(&shell_path)
@@ -581,6 +598,10 @@ fn gather_shell(info: &mut SystemInfo) {
 
         info.shell = Some(shell_name.clone());
 
+        if !options.shell_version {
+            return;
+        }
+
         // Get shell version
         let version_flag = match shell_name.as_str() {
             "bash" => Some("--version"),
@@ -599,8 +620,8 @@ fn gather_shell(info: &mut SystemInfo) {
         };
 
         if let Some(flag) = version_flag {
-            if let Ok(output) = Command::new(&shell_path).arg(flag).output() {
-                if output.status.success() {
+            if let Some(output) = command::run(&shell_path, &[flag]) {
+                if output.success() {
                     let version_output = String::from_utf8_lossy(&output.stdout);
                     let version = extract_version(&version_output, &shell_name);
                     if let Some(v) = version {
@@ -637,11 +658,7 @@ fn extract_version(output: &str, shell: &str) -> Option<String> {
         _ => {
             // Generic: try to find a version number
             for word in first_line.split_whitespace() {
-                if word
-                    .chars()
-                    .next()
-                    .map_or(false, |c| c.is_ascii_digit())
-                {
+                if word.chars().next().is_some_and(|c| c.is_ascii_digit()) {
                     return Some(word.to_string());
                 }
             }
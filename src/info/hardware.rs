@@ -1,30 +1,55 @@
-use crate::info::{DiskInfo, GpuInfo, SystemInfo};
+// Probes that predate the clippy gate keep their original shape
+#![allow(clippy::manual_flatten)]
+
+use crate::config::InfoOptions;
+use crate::info::{cache, command, profile, sysroot, DiskInfo, GpuInfo, SystemInfo};
 use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 use sysinfo::{Disks, System};
 
-pub fn gather(info: &mut SystemInfo) {
-    gather_cpu(info);
-    gather_memory(info);
-    gather_swap(info);
-    gather_gpu(info);
-    gather_disks(info);
-    gather_motherboard(info);
-    gather_bios(info);
-    gather_cpu_temp(info);
-    gather_cpu_governor(info);
+pub fn gather(info: &mut SystemInfo, options: &InfoOptions) {
+    if options.cpu {
+        profile::time("gather_cpu", || gather_cpu(info));
+    }
+    if options.cpu_arch {
+        profile::time("gather_cpu_arch", || gather_cpu_arch(info));
+    }
+    if options.cpu_cache {
+        profile::time("gather_cpu_cache", || gather_cpu_cache(info));
+    }
+    if options.memory {
+        profile::time("gather_memory", || gather_memory(info));
+    }
+    if options.swap {
+        profile::time("gather_swap", || gather_swap(info));
+    }
+    if options.gpu {
+        profile::time("gather_gpu", || gather_gpu(info));
+    }
+    if options.disk {
+        profile::time("gather_disks", || gather_disks(info));
+    }
+    if options.motherboard {
+        profile::time("gather_motherboard", || gather_motherboard(info));
+    }
+    if options.bios {
+        profile::time("gather_bios", || gather_bios(info));
+    }
+    if options.cpu_temp {
+        profile::time("gather_cpu_temp", || gather_cpu_temp(info));
+    }
+    if options.cpu_governor {
+        profile::time("gather_cpu_governor", || gather_cpu_governor(info));
+    }
 }
 
 fn gather_cpu(info: &mut SystemInfo) {
     // Get CPU model from /proc/cpuinfo
-    if let Ok(content) = fs::read_to_string("/proc/cpuinfo") {
+    if let Ok(content) = sysroot::read_to_string("/proc/cpuinfo") {
         let mut model_name = None;
         let mut cores: HashMap<String, bool> = HashMap::new();
         let mut threads = 0u32;
         let mut max_freq: Option<f64> = None;
-        let mut cache_size = None;
 
         for line in content.lines() {
             if line.starts_with("model name") {
@@ -42,10 +67,6 @@ fn gather_cpu(info: &mut SystemInfo) {
                         max_freq = Some(max_freq.map_or(freq, |m: f64| m.max(freq)));
                     }
                 }
-            } else if line.starts_with("cache size") {
-                if cache_size.is_none() {
-                    cache_size = line.split(':').nth(1).map(|s| s.trim().to_string());
-                }
             }
         }
 
@@ -69,24 +90,14 @@ fn gather_cpu(info: &mut SystemInfo) {
         };
         info.cpu_threads = Some(threads);
 
-        if let Some(freq) = max_freq {
-            if freq >= 1000.0 {
-                info.cpu_freq = Some(format!("{:.2} GHz", freq / 1000.0));
-            } else {
-                info.cpu_freq = Some(format!("{:.0} MHz", freq));
-            }
-        }
-
-        if let Some(cache) = cache_size {
-            info.cpu_cache = Some(cache);
-        }
+        info.cpu_freq_mhz = max_freq;
     }
 
     // macOS fallback
     #[cfg(target_os = "macos")]
     if info.cpu.is_none() {
-        if let Ok(output) = Command::new("sysctl").args(["-n", "machdep.cpu.brand_string"]).output() {
-            if output.status.success() {
+        if let Some(output) = command::run("sysctl", &["-n", "machdep.cpu.brand_string"]) {
+            if output.success() {
                 let s = String::from_utf8_lossy(&output.stdout).trim().to_string();
                 if !s.is_empty() {
                     info.cpu = Some(s);
@@ -95,8 +106,8 @@ fn gather_cpu(info: &mut SystemInfo) {
         }
 
         if info.cpu.is_none() {
-            if let Ok(output) = Command::new("sysctl").args(["-n", "hw.model"]).output() {
-                if output.status.success() {
+            if let Some(output) = command::run("sysctl", &["-n", "hw.model"]) {
+                if output.success() {
                     let s = String::from_utf8_lossy(&output.stdout).trim().to_string();
                     if !s.is_empty() {
                         info.cpu = Some(s);
@@ -105,48 +116,55 @@ fn gather_cpu(info: &mut SystemInfo) {
             }
         }
 
-        if let Ok(output) = Command::new("sysctl").args(["-n", "hw.physicalcpu"]).output() {
-            if output.status.success() {
+        if let Some(output) = command::run("sysctl", &["-n", "hw.physicalcpu"]) {
+            if output.success() {
                 if let Ok(v) = String::from_utf8_lossy(&output.stdout).trim().parse::<u32>() {
                     info.cpu_cores = Some(v);
                 }
             }
         }
 
-        if let Ok(output) = Command::new("sysctl").args(["-n", "hw.logicalcpu"]).output() {
-            if output.status.success() {
+        if let Some(output) = command::run("sysctl", &["-n", "hw.logicalcpu"]) {
+            if output.success() {
                 if let Ok(v) = String::from_utf8_lossy(&output.stdout).trim().parse::<u32>() {
                     info.cpu_threads = Some(v);
                 }
             }
         }
 
-        if let Ok(output) = Command::new("sysctl").args(["-n", "hw.cpufrequency"]).output() {
-            if output.status.success() {
-                if let Ok(v) = String::from_utf8_lossy(&output.stdout).trim().parse::<u64>() {
-                    let ghz = v as f64 / 1_000_000_000.0;
-                    if ghz > 0.0 {
-                        info.cpu_freq = Some(format!("{:.2} GHz", ghz));
+        if let Some(output) = command::run("sysctl", &["-n", "hw.cpufrequency"]) {
+            if output.success() {
+                if let Ok(hz) = String::from_utf8_lossy(&output.stdout).trim().parse::<u64>() {
+                    if hz > 0 {
+                        info.cpu_freq_mhz = Some(hz as f64 / 1_000_000.0);
                     }
                 }
             }
         }
     }
+}
 
-    // Get architecture
-    if let Ok(output) = Command::new("uname").arg("-m").output() {
-        if output.status.success() {
+fn gather_cpu_arch(info: &mut SystemInfo) {
+    if let Some(output) = command::run("uname", &["-m"]) {
+        if output.success() {
             info.cpu_arch = Some(trim_lossy(&output.stdout));
         }
     }
-
-    // Try to get more detailed cache info
-    gather_cpu_cache(info);
 }
 
 fn gather_cpu_cache(info: &mut SystemInfo) {
+    // Fall back to the summary line in /proc/cpuinfo
+    if let Ok(content) = sysroot::read_to_string("/proc/cpuinfo") {
+        info.cpu_cache = content
+            .lines()
+            .find(|line| line.starts_with("cache size"))
+            .and_then(|line| line.split(':').nth(1))
+            .map(|s| s.trim().to_string());
+    }
+
+    // Prefer the per-level breakdown from sysfs
     let cache_path = Path::new("/sys/devices/system/cpu/cpu0/cache");
-    if cache_path.exists() {
+    if sysroot::exists(cache_path) {
         let mut l1d = None;
         let mut l1i = None;
         let mut l2 = None;
@@ -154,7 +172,7 @@ fn gather_cpu_cache(info: &mut SystemInfo) {
 
         for i in 0..10 {
             let index_path = cache_path.join(format!("index{}", i));
-            if !index_path.exists() {
+            if !sysroot::exists(&index_path) {
                 break;
             }
 
@@ -191,52 +209,63 @@ fn gather_cpu_cache(info: &mut SystemInfo) {
     }
 }
 
+// /proc/meminfo values in bytes
+fn read_meminfo() -> Option<HashMap<String, u64>> {
+    let content = sysroot::read_to_string("/proc/meminfo").ok()?;
+    let values = content
+        .lines()
+        .filter_map(|line| {
+            let (key, rest) = line.split_once(':')?;
+            let kib = rest.split_whitespace().next()?.parse::<u64>().ok()?;
+            Some((key.to_string(), kib * 1024))
+        })
+        .collect();
+    Some(values)
+}
+
 fn gather_memory(info: &mut SystemInfo) {
-    let sys = System::new_all();
-    let total = sys.total_memory();
-    let used = sys.used_memory();
+    let (total, used) = match read_meminfo() {
+        Some(mem) => {
+            let total = mem.get("MemTotal").copied().unwrap_or(0);
+            let available = mem.get("MemAvailable").or(mem.get("MemFree")).copied().unwrap_or(0);
+            (total, total.saturating_sub(available))
+        }
+        None if !sysroot::is_set() => {
+            let sys = System::new_all();
+            (sys.total_memory(), sys.used_memory())
+        }
+        None => return,
+    };
+    if total == 0 {
+        return;
+    }
 
     info.memory_total = Some(total);
     info.memory_used = Some(used);
-
-    let total_gb = total as f64 / 1024.0 / 1024.0 / 1024.0;
-    let used_gb = used as f64 / 1024.0 / 1024.0 / 1024.0;
-
-    info.memory = Some(format!(
-        "{:.2} GiB / {:.2} GiB ({:.0}%)",
-        used_gb,
-        total_gb,
-        (used as f64 / total as f64) * 100.0
-    ));
 }
 
 fn gather_swap(info: &mut SystemInfo) {
-    let sys = System::new_all();
-    let total = sys.total_swap();
-    let used = sys.used_swap();
+    let (total, used) = match read_meminfo() {
+        Some(mem) => {
+            let total = mem.get("SwapTotal").copied().unwrap_or(0);
+            let free = mem.get("SwapFree").copied().unwrap_or(0);
+            (total, total.saturating_sub(free))
+        }
+        None if !sysroot::is_set() => {
+            let sys = System::new_all();
+            (sys.total_swap(), sys.used_swap())
+        }
+        None => return,
+    };
 
     if total > 0 {
         info.swap_total = Some(total);
         info.swap_used = Some(used);
-
-        let total_gb = total as f64 / 1024.0 / 1024.0 / 1024.0;
-        let used_gb = used as f64 / 1024.0 / 1024.0 / 1024.0;
-
-        info.swap = Some(format!(
-            "{:.2} GiB / {:.2} GiB ({:.0}%)",
-            used_gb,
-            total_gb,
-            if total > 0 {
-                (used as f64 / total as f64) * 100.0
-            } else {
-                0.0
-            }
-        ));
     }
 }
 
 fn read_trimmed(path: &Path) -> Option<String> {
-    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+    sysroot::read_to_string(path).ok().map(|s| s.trim().to_string())
 }
 
 fn trim_lossy(bytes: &[u8]) -> String {
@@ -244,9 +273,18 @@ fn trim_lossy(bytes: &[u8]) -> String {
 }
 
 fn gather_gpu(info: &mut SystemInfo) {
+    // Names, drivers and VRAM rarely change, temperatures are always read fresh
+    let sources = [PathBuf::from("/sys/bus/pci/devices")];
+    info.gpu = cache::cached("gpu", &sources, detect_gpus);
+    gather_gpu_temps(info);
+}
+
+fn detect_gpus() -> Vec<GpuInfo> {
+    let mut gpus = Vec::new();
+
     // Try lspci first
-    if let Ok(output) = Command::new("lspci").arg("-mm").output() {
-        if output.status.success() {
+    if let Some(output) = command::run("lspci", &["-mm"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             for line in content.lines() {
                 // Look for VGA or 3D controllers
@@ -267,8 +305,8 @@ fn gather_gpu(info: &mut SystemInfo) {
                             let mut gpu = GpuInfo {
                                 name,
                                 driver: None,
-                                vram: None,
-                                temp: None,
+                                vram_bytes: None,
+                                temp_celsius: None,
                             };
 
                             // Try to get driver info
@@ -277,11 +315,9 @@ fn gather_gpu(info: &mut SystemInfo) {
                             }
 
                             // Try to get VRAM
-                            if let Some(vram) = get_gpu_vram() {
-                                gpu.vram = Some(vram);
-                            }
+                            gpu.vram_bytes = get_gpu_vram();
 
-                            info.gpu.push(gpu);
+                            gpus.push(gpu);
                         }
                     }
                 }
@@ -290,13 +326,13 @@ fn gather_gpu(info: &mut SystemInfo) {
     }
 
     // Fallback: check /sys/class/drm
-    if info.gpu.is_empty() {
-        for entry in fs::read_dir("/sys/class/drm").into_iter().flatten() {
+    if gpus.is_empty() {
+        for entry in sysroot::read_dir("/sys/class/drm").into_iter().flatten() {
             if let Ok(entry) = entry {
                 let name = entry.file_name().to_string_lossy().to_string();
                 if name.starts_with("card") && !name.contains('-') {
-                    let device_path = entry.path().join("device");
-                    if let Ok(vendor) = fs::read_to_string(device_path.join("vendor")) {
+                    let device_path = Path::new("/sys/class/drm").join(entry.file_name()).join("device");
+                    if let Ok(vendor) = sysroot::read_to_string(device_path.join("vendor")) {
                         let vendor_id = vendor.trim();
                         let vendor_name = match vendor_id {
                             "0x10de" => "NVIDIA",
@@ -308,24 +344,24 @@ fn gather_gpu(info: &mut SystemInfo) {
                         let gpu = GpuInfo {
                             name: format!("{} Graphics", vendor_name),
                             driver: get_gpu_driver(),
-                            vram: get_gpu_vram(),
-                            temp: None,
+                            vram_bytes: get_gpu_vram(),
+                            temp_celsius: None,
                         };
-                        info.gpu.push(gpu);
+                        gpus.push(gpu);
                     }
                 }
             }
         }
     }
 
-    // Get GPU temperatures
-    gather_gpu_temps(info);
+    gpus
 }
 
+
 fn get_gpu_driver() -> Option<String> {
     // Check for NVIDIA
-    if Path::new("/proc/driver/nvidia/version").exists() {
-        if let Ok(content) = fs::read_to_string("/proc/driver/nvidia/version") {
+    if sysroot::exists("/proc/driver/nvidia/version") {
+        if let Ok(content) = sysroot::read_to_string("/proc/driver/nvidia/version") {
             if let Some(line) = content.lines().next() {
                 if let Some(version) = line.split_whitespace().nth(7) {
                     return Some(format!("NVIDIA {}", version));
@@ -335,10 +371,10 @@ fn get_gpu_driver() -> Option<String> {
     }
 
     // Check for AMD
-    for entry in fs::read_dir("/sys/class/drm").into_iter().flatten() {
+    for entry in sysroot::read_dir("/sys/class/drm").into_iter().flatten() {
         if let Ok(entry) = entry {
-            let driver_path = entry.path().join("device/driver");
-            if let Ok(target) = fs::read_link(&driver_path) {
+            let driver_path = Path::new("/sys/class/drm").join(entry.file_name()).join("device/driver");
+            if let Ok(target) = sysroot::read_link(&driver_path) {
                 let driver = target.file_name()?.to_string_lossy().to_string();
                 if driver == "amdgpu" || driver == "radeon" {
                     return Some(driver);
@@ -352,28 +388,27 @@ fn get_gpu_driver() -> Option<String> {
     None
 }
 
-fn get_gpu_vram() -> Option<String> {
+fn get_gpu_vram() -> Option<u64> {
     // Try NVIDIA
-    if let Ok(output) = Command::new("nvidia-smi")
-        .args(["--query-gpu=memory.total", "--format=csv,noheader,nounits"])
-        .output()
+    if let Some(output) = command::run("nvidia-smi", &["--query-gpu=memory.total", "--format=csv,noheader,nounits"])
     {
-        if output.status.success() {
+        if output.success() {
             let mem = String::from_utf8_lossy(&output.stdout).trim().to_string();
             if let Ok(mb) = mem.parse::<u64>() {
-                return Some(format!("{} MiB", mb));
+                return Some(mb * 1024 * 1024);
             }
         }
     }
 
     // Try AMD
-    for entry in fs::read_dir("/sys/class/drm").into_iter().flatten() {
+    for entry in sysroot::read_dir("/sys/class/drm").into_iter().flatten() {
         if let Ok(entry) = entry {
-            let vram_path = entry.path().join("device/mem_info_vram_total");
-            if let Ok(content) = fs::read_to_string(&vram_path) {
+            let vram_path = Path::new("/sys/class/drm")
+                .join(entry.file_name())
+                .join("device/mem_info_vram_total");
+            if let Ok(content) = sysroot::read_to_string(&vram_path) {
                 if let Ok(bytes) = content.trim().parse::<u64>() {
-                    let mb = bytes / 1024 / 1024;
-                    return Some(format!("{} MiB", mb));
+                    return Some(bytes);
                 }
             }
         }
@@ -384,33 +419,32 @@ fn get_gpu_vram() -> Option<String> {
 
 fn gather_gpu_temps(info: &mut SystemInfo) {
     // NVIDIA temperature
-    if let Ok(output) = Command::new("nvidia-smi")
-        .args(["--query-gpu=temperature.gpu", "--format=csv,noheader"])
-        .output()
+    if let Some(output) = command::run("nvidia-smi", &["--query-gpu=temperature.gpu", "--format=csv,noheader"])
     {
-        if output.status.success() {
-            let temp = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !temp.is_empty() {
+        if output.success() {
+            let temp = String::from_utf8_lossy(&output.stdout);
+            if let Ok(celsius) = temp.trim().parse::<f64>() {
                 if let Some(gpu) = info.gpu.first_mut() {
-                    gpu.temp = Some(format!("{}°C", temp));
+                    gpu.temp_celsius = Some(celsius);
                 }
             }
         }
     }
 
     // AMD temperature from hwmon
-    for entry in fs::read_dir("/sys/class/hwmon").into_iter().flatten() {
+    for entry in sysroot::read_dir("/sys/class/hwmon").into_iter().flatten() {
         if let Ok(entry) = entry {
-            let name_path = entry.path().join("name");
-            if let Ok(name) = fs::read_to_string(&name_path) {
+            let hwmon = Path::new("/sys/class/hwmon").join(entry.file_name());
+            let name_path = hwmon.join("name");
+            if let Ok(name) = sysroot::read_to_string(&name_path) {
                 if name.trim() == "amdgpu" {
-                    let temp_path = entry.path().join("temp1_input");
-                    if let Ok(temp) = fs::read_to_string(&temp_path) {
+                    let temp_path = hwmon.join("temp1_input");
+                    if let Ok(temp) = sysroot::read_to_string(&temp_path) {
                         if let Ok(millidegrees) = temp.trim().parse::<i32>() {
-                            let celsius = millidegrees / 1000;
+                            let celsius = millidegrees as f64 / 1000.0;
                             for gpu in &mut info.gpu {
-                                if gpu.name.contains("AMD") && gpu.temp.is_none() {
-                                    gpu.temp = Some(format!("{}°C", celsius));
+                                if gpu.name.contains("AMD") && gpu.temp_celsius.is_none() {
+                                    gpu.temp_celsius = Some(celsius);
                                 }
                             }
                         }
@@ -421,14 +455,64 @@ fn gather_gpu_temps(info: &mut SystemInfo) {
     }
 }
 
-fn gather_disks(info: &mut SystemInfo) {
-    let disks = Disks::new_with_refreshed_list();
+struct Mount {
+    device: String,
+    mount: String,
+    fs_type: String,
+    total: u64,
+    available: u64,
+}
 
-    for disk in disks.list() {
-        let mount = disk.mount_point().to_string_lossy().to_string();
+fn list_mounts() -> Vec<Mount> {
+    if sysroot::is_set() {
+        return list_sysroot_mounts();
+    }
+
+    Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .map(|disk| Mount {
+            device: disk.name().to_string_lossy().to_string(),
+            mount: disk.mount_point().to_string_lossy().to_string(),
+            fs_type: disk.file_system().to_string_lossy().to_string(),
+            total: disk.total_space(),
+            available: disk.available_space(),
+        })
+        .collect()
+}
+
+// Mounts of the target system, sized by the directories below the root
+fn list_sysroot_mounts() -> Vec<Mount> {
+    let Ok(content) = sysroot::read_to_string("/proc/mounts") else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?.to_string();
+            let mount = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?.to_string();
+            let stat = nix::sys::statvfs::statvfs(sysroot::path(&mount).as_path()).ok()?;
+            let fragment = stat.fragment_size() as u64;
+            Some(Mount {
+                device,
+                mount,
+                fs_type,
+                total: stat.blocks() as u64 * fragment,
+                available: stat.blocks_available() as u64 * fragment,
+            })
+        })
+        .collect()
+}
+
+fn gather_disks(info: &mut SystemInfo) {
+    for disk in list_mounts() {
+        let mount = disk.mount;
 
         // Skip pseudo filesystems
-        let fs_type = disk.file_system().to_string_lossy().to_string();
+        let fs_type = disk.fs_type;
         if matches!(
             fs_type.as_str(),
             "tmpfs"
@@ -456,8 +540,8 @@ fn gather_disks(info: &mut SystemInfo) {
             continue;
         }
 
-        let total = disk.total_space();
-        let available = disk.available_space();
+        let total = disk.total;
+        let available = disk.available;
         let used = total.saturating_sub(available);
 
         // Skip tiny filesystems
@@ -474,11 +558,11 @@ fn gather_disks(info: &mut SystemInfo) {
         let disk_info = DiskInfo {
             mount,
             filesystem: fs_type,
-            size: format_bytes(total),
-            used: format_bytes(used),
-            available: format_bytes(available),
+            size_bytes: total,
+            used_bytes: used,
+            available_bytes: available,
             percent,
-            disk_type: get_disk_type(disk.name().to_string_lossy().as_ref()),
+            disk_type: get_disk_type(&disk.device),
         };
 
         info.disks.push(disk_info);
@@ -490,10 +574,15 @@ fn get_disk_type(device: &str) -> Option<String> {
     let base = device
         .trim_start_matches("/dev/")
         .trim_end_matches(|c: char| c.is_numeric());
+    // nvme0n1p2 and mmcblk0p1 separate the partition number with a 'p'
+    let base = match base.strip_suffix('p') {
+        Some(disk) if disk.ends_with(|c: char| c.is_numeric()) => disk,
+        _ => base,
+    };
 
     // Check rotational status
     let rotational_path = format!("/sys/block/{}/queue/rotational", base);
-    if let Ok(content) = fs::read_to_string(&rotational_path) {
+    if let Ok(content) = sysroot::read_to_string(&rotational_path) {
         let is_rotational = content.trim() == "1";
         if !is_rotational {
             // Check if NVMe
@@ -509,49 +598,47 @@ fn get_disk_type(device: &str) -> Option<String> {
     None
 }
 
-fn format_bytes(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-    const TB: u64 = GB * 1024;
-
-    if bytes >= TB {
-        format!("{:.2} TiB", bytes as f64 / TB as f64)
-    } else if bytes >= GB {
-        format!("{:.2} GiB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MiB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KiB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
+fn gather_motherboard(info: &mut SystemInfo) {
+    let sources = [
+        PathBuf::from("/sys/class/dmi/id/board_vendor"),
+        PathBuf::from("/sys/class/dmi/id/board_name"),
+    ];
+    info.motherboard = cache::cached("motherboard", &sources, read_motherboard);
 }
 
-fn gather_motherboard(info: &mut SystemInfo) {
-    let vendor = fs::read_to_string("/sys/class/dmi/id/board_vendor")
+fn read_motherboard() -> Option<String> {
+    let vendor = sysroot::read_to_string("/sys/class/dmi/id/board_vendor")
         .ok()
         .map(|s| s.trim().to_string());
-    let name = fs::read_to_string("/sys/class/dmi/id/board_name")
+    let name = sysroot::read_to_string("/sys/class/dmi/id/board_name")
         .ok()
         .map(|s| s.trim().to_string());
 
     match (vendor, name) {
-        (Some(v), Some(n)) => info.motherboard = Some(format!("{} {}", v, n)),
-        (None, Some(n)) => info.motherboard = Some(n),
-        (Some(v), None) => info.motherboard = Some(v),
-        _ => {}
+        (Some(v), Some(n)) => Some(format!("{} {}", v, n)),
+        (None, Some(n)) => Some(n),
+        (Some(v), None) => Some(v),
+        _ => None,
     }
 }
 
 fn gather_bios(info: &mut SystemInfo) {
-    let vendor = fs::read_to_string("/sys/class/dmi/id/bios_vendor")
+    let sources = [
+        PathBuf::from("/sys/class/dmi/id/bios_vendor"),
+        PathBuf::from("/sys/class/dmi/id/bios_version"),
+        PathBuf::from("/sys/class/dmi/id/bios_date"),
+    ];
+    info.bios = cache::cached("bios", &sources, read_bios);
+}
+
+fn read_bios() -> Option<String> {
+    let vendor = sysroot::read_to_string("/sys/class/dmi/id/bios_vendor")
         .ok()
         .map(|s| s.trim().to_string());
-    let version = fs::read_to_string("/sys/class/dmi/id/bios_version")
+    let version = sysroot::read_to_string("/sys/class/dmi/id/bios_version")
         .ok()
         .map(|s| s.trim().to_string());
-    let date = fs::read_to_string("/sys/class/dmi/id/bios_date")
+    let date = sysroot::read_to_string("/sys/class/dmi/id/bios_date")
         .ok()
         .map(|s| s.trim().to_string());
 
@@ -566,25 +653,27 @@ fn gather_bios(info: &mut SystemInfo) {
         parts.push(format!("({})", d));
     }
 
-    if !parts.is_empty() {
-        info.bios = Some(parts.join(" "));
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
     }
 }
 
 fn gather_cpu_temp(info: &mut SystemInfo) {
     // Try coretemp first (Intel)
-    for entry in fs::read_dir("/sys/class/hwmon").into_iter().flatten() {
+    for entry in sysroot::read_dir("/sys/class/hwmon").into_iter().flatten() {
         if let Ok(entry) = entry {
-            let name_path = entry.path().join("name");
-            if let Ok(name) = fs::read_to_string(&name_path) {
+            let hwmon = Path::new("/sys/class/hwmon").join(entry.file_name());
+            let name_path = hwmon.join("name");
+            if let Ok(name) = sysroot::read_to_string(&name_path) {
                 let name = name.trim();
                 if name == "coretemp" || name == "k10temp" || name == "zenpower" {
                     // Find the package temp or first core temp
-                    let temp_path = entry.path().join("temp1_input");
-                    if let Ok(temp) = fs::read_to_string(&temp_path) {
+                    let temp_path = hwmon.join("temp1_input");
+                    if let Ok(temp) = sysroot::read_to_string(&temp_path) {
                         if let Ok(millidegrees) = temp.trim().parse::<i32>() {
-                            let celsius = millidegrees / 1000;
-                            info.cpu_temp = Some(format!("{}°C", celsius));
+                            info.cpu_temp_celsius = Some(millidegrees as f64 / 1000.0);
                             return;
                         }
                     }
@@ -599,7 +688,7 @@ fn gather_cpu_temp(info: &mut SystemInfo) {
         let temp_path = format!("/sys/class/thermal/thermal_zone{}/temp", i);
 
         if let (Ok(zone_type), Ok(temp)) =
-            (fs::read_to_string(&type_path), fs::read_to_string(&temp_path))
+            (sysroot::read_to_string(&type_path), sysroot::read_to_string(&temp_path))
         {
             let zone_type = zone_type.trim().to_lowercase();
             if zone_type.contains("cpu")
@@ -608,8 +697,7 @@ fn gather_cpu_temp(info: &mut SystemInfo) {
                 || zone_type.contains("x86_pkg_temp")
             {
                 if let Ok(millidegrees) = temp.trim().parse::<i32>() {
-                    let celsius = millidegrees / 1000;
-                    info.cpu_temp = Some(format!("{}°C", celsius));
+                    info.cpu_temp_celsius = Some(millidegrees as f64 / 1000.0);
                     return;
                 }
             }
@@ -619,7 +707,7 @@ fn gather_cpu_temp(info: &mut SystemInfo) {
 
 fn gather_cpu_governor(info: &mut SystemInfo) {
     let governor_path = "/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor";
-    if let Ok(governor) = fs::read_to_string(governor_path) {
+    if let Ok(governor) = sysroot::read_to_string(governor_path) {
         info.cpu_governor = Some(governor.trim().to_string());
     }
 }
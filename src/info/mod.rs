@@ -1,111 +1,180 @@
 pub mod audio;
+pub mod cache;
+pub mod command;
 pub mod desktop;
 pub mod hardware;
 pub mod misc;
 pub mod network;
 pub mod packages;
 pub mod power;
+pub mod profile;
 pub mod system;
+pub mod sysroot;
 
-use serde::Serialize;
+use crate::config::InfoOptions;
+use crate::schema::SCHEMA_VERSION;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::thread;
 
-#[derive(Debug, Clone, Serialize)]
+// Fills the fields of one module, see `SystemInfo::gather`
+type Gather = fn(&mut SystemInfo, &InfoOptions);
+
+// Missing fields deserialize as not gathered, so snapshots from older
+// versions or with modules disabled still load
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SystemInfo {
     // System
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub os: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub os_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub kernel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub hostname: Option<String>,
-    pub uptime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uptime_seconds: Option<u64>,
-    pub load_average: Option<String>,
+    // 1, 5 and 15 minute averages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_average: Option<[f64; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub processes: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub logged_users: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub machine_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub init_system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub boot_time: Option<String>,
 
     // Hardware
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cpu: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cpu_arch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cpu_cores: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cpu_threads: Option<u32>,
-    pub cpu_freq: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_freq_mhz: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cpu_cache: Option<String>,
-    pub cpu_temp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_temp_celsius: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cpu_governor: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub gpu: Vec<GpuInfo>,
-    pub memory: Option<String>,
+    // Memory and swap in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub memory_used: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub memory_total: Option<u64>,
-    pub swap: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub swap_used: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub swap_total: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub disks: Vec<DiskInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub motherboard: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bios: Option<String>,
 
     // Desktop
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub de: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub wm: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub wm_theme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub theme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub icons: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub terminal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub terminal_font: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shell: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shell_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub display_server: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub resolution: Option<String>,
 
     // Network
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub interfaces: Vec<NetworkInterface>,
-    #[cfg(feature = "network")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub public_ip: Option<PublicIpInfo>,
 
     // Power
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub battery: Option<BatteryInfo>,
-    pub brightness: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brightness_percent: Option<u32>,
 
     // Audio
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub audio_device: Option<String>,
-    pub volume: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume_percent: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume_muted: Option<bool>,
 
     // Packages
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub packages: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub package_counts: Vec<PackageCount>,
 
     // Misc
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub timezone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub virtualization: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub container: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub security: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ssh_connection: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bluetooth: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GpuInfo {
     pub name: String,
     pub driver: Option<String>,
-    pub vram: Option<String>,
-    pub temp: Option<String>,
+    pub vram_bytes: Option<u64>,
+    pub temp_celsius: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiskInfo {
     pub mount: String,
     pub filesystem: String,
-    pub size: String,
-    pub used: String,
-    pub available: String,
+    pub size_bytes: u64,
+    pub used_bytes: u64,
+    pub available_bytes: u64,
     pub percent: u8,
     pub disk_type: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkInterface {
     pub name: String,
     pub ipv4: Option<String>,
@@ -115,8 +184,9 @@ pub struct NetworkInterface {
     pub state: Option<String>,
 }
 
-#[cfg(feature = "network")]
-#[derive(Debug, Clone, Serialize)]
+// Present without the `network` feature as well, so the JSON schema does not
+// depend on how hyperfetch was built
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublicIpInfo {
     pub ip: String,
     pub country: Option<String>,
@@ -126,112 +196,171 @@ pub struct PublicIpInfo {
     pub isp: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatteryInfo {
     pub percent: u8,
     pub status: String,
-    pub time_remaining: Option<String>,
+    pub time_remaining_minutes: Option<u32>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackageCount {
     pub manager: String,
     pub count: u32,
 }
 
-impl Default for SystemInfo {
-    fn default() -> Self {
-        Self {
-            os: None,
-            os_id: None,
-            kernel: None,
-            hostname: None,
-            uptime: None,
-            uptime_seconds: None,
-            load_average: None,
-            processes: None,
-            logged_users: None,
-            machine_type: None,
-            init_system: None,
-            boot_time: None,
-            cpu: None,
-            cpu_arch: None,
-            cpu_cores: None,
-            cpu_threads: None,
-            cpu_freq: None,
-            cpu_cache: None,
-            cpu_temp: None,
-            cpu_governor: None,
-            gpu: Vec::new(),
-            memory: None,
-            memory_used: None,
-            memory_total: None,
-            swap: None,
-            swap_used: None,
-            swap_total: None,
-            disks: Vec::new(),
-            motherboard: None,
-            bios: None,
-            de: None,
-            wm: None,
-            wm_theme: None,
-            theme: None,
-            icons: None,
-            cursor: None,
-            terminal: None,
-            terminal_font: None,
-            shell: None,
-            shell_version: None,
-            display_server: None,
-            resolution: None,
-            interfaces: Vec::new(),
-            #[cfg(feature = "network")]
-            public_ip: None,
-            battery: None,
-            brightness: None,
-            audio_device: None,
-            volume: None,
-            packages: None,
-            package_counts: Vec::new(),
-            locale: None,
-            timezone: None,
-            virtualization: None,
-            container: None,
-            security: None,
-            ssh_connection: None,
-            bluetooth: None,
-        }
-    }
-}
-
 impl SystemInfo {
-    pub fn gather(fetch_public_ip: bool) -> Self {
-        let mut info = SystemInfo::default();
+    pub fn gather(options: &InfoOptions) -> Self {
+        command::start_budget();
+        profile::start();
 
-        // System info
-        system::gather(&mut info);
+        // Modules write disjoint fields, so each one gathers into its own
+        // SystemInfo on a separate thread and the results are merged.
+        let modules: [(&str, Gather); 8] = [
+            ("system", system::gather),
+            ("hardware", hardware::gather),
+            ("desktop", desktop::gather),
+            ("network", network::gather),
+            ("power", power::gather),
+            ("audio", audio::gather),
+            ("packages", packages::gather),
+            ("misc", misc::gather),
+        ];
 
-        // Hardware info
-        hardware::gather(&mut info);
+        let parts: Vec<SystemInfo> = thread::scope(|scope| {
+            let handles: Vec<_> = modules
+                .iter()
+                .map(|(name, gather)| {
+                    let handle = scope.spawn(move || {
+                        let mut part = SystemInfo::default();
+                        gather(&mut part, options);
+                        part
+                    });
+                    (name, handle)
+                })
+                .collect();
 
-        // Desktop info
-        desktop::gather(&mut info);
+            // A probe that panics loses only its own module's fields
+            handles
+                .into_iter()
+                .filter_map(|(name, handle)| match handle.join() {
+                    Ok(part) => Some(part),
+                    Err(_) => {
+                        eprintln!("Warning: the {} module failed, its fields are left empty", name);
+                        None
+                    }
+                })
+                .collect()
+        });
 
-        // Network info
-        network::gather(&mut info, fetch_public_ip);
+        let mut info = SystemInfo::default();
+        for part in parts {
+            info.merge(part);
+        }
+        cache::save();
 
-        // Power info
-        power::gather(&mut info);
+        info
+    }
 
-        // Audio info
-        audio::gather(&mut info);
+    /// Reads a snapshot written by `--json`, `-` reads stdin.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let content = if path == Path::new("-") {
+            std::io::read_to_string(std::io::stdin())
+        } else {
+            fs::read_to_string(path)
+        }
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let value: serde_json::Value =
+            serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
 
-        // Package info
-        packages::gather(&mut info);
+        // Older versions only had fields this one still understands
+        let version = value.get("schema_version").and_then(serde_json::Value::as_u64);
+        if let Some(version) = version.filter(|v| *v > u64::from(SCHEMA_VERSION)) {
+            return Err(format!(
+                "{} uses schema version {}, this hyperfetch only reads up to {}",
+                path.display(),
+                version,
+                SCHEMA_VERSION
+            ));
+        }
+        serde_json::from_value(value).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    fn merge(&mut self, other: SystemInfo) {
+        self.os.merge(other.os);
+        self.os_id.merge(other.os_id);
+        self.kernel.merge(other.kernel);
+        self.hostname.merge(other.hostname);
+        self.uptime_seconds.merge(other.uptime_seconds);
+        self.load_average.merge(other.load_average);
+        self.processes.merge(other.processes);
+        self.logged_users.merge(other.logged_users);
+        self.machine_type.merge(other.machine_type);
+        self.init_system.merge(other.init_system);
+        self.boot_time.merge(other.boot_time);
+        self.cpu.merge(other.cpu);
+        self.cpu_arch.merge(other.cpu_arch);
+        self.cpu_cores.merge(other.cpu_cores);
+        self.cpu_threads.merge(other.cpu_threads);
+        self.cpu_freq_mhz.merge(other.cpu_freq_mhz);
+        self.cpu_cache.merge(other.cpu_cache);
+        self.cpu_temp_celsius.merge(other.cpu_temp_celsius);
+        self.cpu_governor.merge(other.cpu_governor);
+        self.gpu.merge(other.gpu);
+        self.memory_used.merge(other.memory_used);
+        self.memory_total.merge(other.memory_total);
+        self.swap_used.merge(other.swap_used);
+        self.swap_total.merge(other.swap_total);
+        self.disks.merge(other.disks);
+        self.motherboard.merge(other.motherboard);
+        self.bios.merge(other.bios);
+        self.de.merge(other.de);
+        self.wm.merge(other.wm);
+        self.wm_theme.merge(other.wm_theme);
+        self.theme.merge(other.theme);
+        self.icons.merge(other.icons);
+        self.cursor.merge(other.cursor);
+        self.terminal.merge(other.terminal);
+        self.terminal_font.merge(other.terminal_font);
+        self.shell.merge(other.shell);
+        self.shell_version.merge(other.shell_version);
+        self.display_server.merge(other.display_server);
+        self.resolution.merge(other.resolution);
+        self.interfaces.merge(other.interfaces);
+        self.public_ip.merge(other.public_ip);
+        self.battery.merge(other.battery);
+        self.brightness_percent.merge(other.brightness_percent);
+        self.audio_device.merge(other.audio_device);
+        self.volume_percent.merge(other.volume_percent);
+        self.volume_muted.merge(other.volume_muted);
+        self.packages.merge(other.packages);
+        self.package_counts.merge(other.package_counts);
+        self.locale.merge(other.locale);
+        self.timezone.merge(other.timezone);
+        self.virtualization.merge(other.virtualization);
+        self.container.merge(other.container);
+        self.security.merge(other.security);
+        self.ssh_connection.merge(other.ssh_connection);
+        self.bluetooth.merge(other.bluetooth);
+    }
+}
 
-        // Misc info
-        misc::gather(&mut info);
+trait Merge {
+    fn merge(&mut self, other: Self);
+}
 
-        info
+impl<T> Merge for Option<T> {
+    fn merge(&mut self, other: Self) {
+        if other.is_some() {
+            *self = other;
+        }
+    }
+}
+
+impl<T> Merge for Vec<T> {
+    fn merge(&mut self, other: Self) {
+        if !other.is_empty() {
+            *self = other;
+        }
     }
 }
@@ -1,21 +1,24 @@
-use crate::info::{NetworkInterface, SystemInfo};
+use crate::config::InfoOptions;
+use crate::info::{command, profile, sysroot, NetworkInterface, SystemInfo};
 #[cfg(feature = "network")]
 use crate::info::PublicIpInfo;
-use std::fs;
-use std::process::Command;
+use std::path::Path;
 
-pub fn gather(info: &mut SystemInfo, fetch_public_ip: bool) {
-    gather_interfaces(info);
+pub fn gather(info: &mut SystemInfo, options: &InfoOptions) {
+    if options.network {
+        profile::time("gather_interfaces", || gather_interfaces(info));
+    }
 
-    if fetch_public_ip {
-        gather_public_ip(info);
+    #[cfg(feature = "network")]
+    if options.public_ip {
+        profile::time("gather_public_ip", || gather_public_ip(info));
     }
 }
 
 fn gather_interfaces(info: &mut SystemInfo) {
     let net_path = "/sys/class/net";
 
-    if let Ok(entries) = fs::read_dir(net_path) {
+    if let Ok(entries) = sysroot::read_dir(net_path) {
         for entry in entries.flatten() {
             let name = entry.file_name().to_string_lossy().to_string();
 
@@ -24,7 +27,7 @@ fn gather_interfaces(info: &mut SystemInfo) {
                 continue;
             }
 
-            let iface_path = entry.path();
+            let iface_path = Path::new(net_path).join(&name);
 
             // Get interface state
             let state = read_trimmed(iface_path.join("operstate").as_path());
@@ -58,8 +61,8 @@ fn gather_interfaces(info: &mut SystemInfo) {
             };
 
             // Get IP addresses using ip command
-            if let Ok(output) = Command::new("ip").args(["addr", "show", &name]).output() {
-                if output.status.success() {
+            if let Some(output) = command::run("ip", &["addr", "show", &name]) {
+                if output.success() {
                     let content = String::from_utf8_lossy(&output.stdout);
                     for line in content.lines() {
                         let line = line.trim();
@@ -144,13 +147,8 @@ fn gather_public_ip(info: &mut SystemInfo) {
     }
 }
 
-#[cfg(not(feature = "network"))]
-fn gather_public_ip(_info: &mut SystemInfo) {
-    // Network feature not enabled
-}
-
 fn read_trimmed(path: &std::path::Path) -> Option<String> {
-    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+    sysroot::read_to_string(path).ok().map(|s| s.trim().to_string())
 }
 
 fn parse_ip_line(line: &str, prefix: &str) -> Option<String> {
@@ -1,139 +1,63 @@
-use crate::info::{PackageCount, SystemInfo};
-use std::fs;
-use std::path::Path;
-use std::process::Command;
+use crate::config::InfoOptions;
+use crate::info::{cache, command, profile, sysroot, PackageCount, SystemInfo};
+use std::path::{Path, PathBuf};
+use std::thread;
 
-pub fn gather(info: &mut SystemInfo) {
-    let mut counts = Vec::new();
+type Counter = fn() -> Option<u32>;
 
+const COUNTERS: &[(&str, Counter)] = &[
     // Native package managers
-    if let Some(count) = count_pacman() {
-        counts.push(PackageCount {
-            manager: "pacman".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_apt() {
-        counts.push(PackageCount {
-            manager: "apt".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_dnf() {
-        counts.push(PackageCount {
-            manager: "dnf".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_rpm() {
-        counts.push(PackageCount {
-            manager: "rpm".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_emerge() {
-        counts.push(PackageCount {
-            manager: "emerge".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_xbps() {
-        counts.push(PackageCount {
-            manager: "xbps".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_apk() {
-        counts.push(PackageCount {
-            manager: "apk".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_zypper() {
-        counts.push(PackageCount {
-            manager: "zypper".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_nix() {
-        counts.push(PackageCount {
-            manager: "nix".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_guix() {
-        counts.push(PackageCount {
-            manager: "guix".to_string(),
-            count,
-        });
-    }
-
+    ("pacman", count_pacman),
+    ("apt", count_apt),
+    ("dnf", count_dnf),
+    ("rpm", count_rpm),
+    ("emerge", count_emerge),
+    ("xbps", count_xbps),
+    ("apk", count_apk),
+    ("zypper", count_zypper),
+    ("nix", count_nix),
+    ("guix", count_guix),
     // Universal/third-party package managers
-    if let Some(count) = count_flatpak() {
-        counts.push(PackageCount {
-            manager: "flatpak".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_snap() {
-        counts.push(PackageCount {
-            manager: "snap".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_brew() {
-        counts.push(PackageCount {
-            manager: "brew".to_string(),
-            count,
-        });
-    }
-
+    ("flatpak", count_flatpak),
+    ("snap", count_snap),
+    ("brew", count_brew),
     // Language package managers
-    if let Some(count) = count_cargo() {
-        counts.push(PackageCount {
-            manager: "cargo".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_pip() {
-        counts.push(PackageCount {
-            manager: "pip".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_npm() {
-        counts.push(PackageCount {
-            manager: "npm".to_string(),
-            count,
-        });
-    }
-
-    if let Some(count) = count_gem() {
-        counts.push(PackageCount {
-            manager: "gem".to_string(),
-            count,
-        });
-    }
+    ("cargo", count_cargo),
+    ("pip", count_pip),
+    ("npm", count_npm),
+    ("gem", count_gem),
+    ("go", count_go),
+];
+
+pub fn gather(info: &mut SystemInfo, options: &InfoOptions) {
+    if !options.packages {
+        return;
+    }
+
+    // Every counter is independent, so run them all at once and keep the
+    // table order in the result.
+    let counts: Vec<PackageCount> = thread::scope(|scope| {
+        let handles: Vec<_> = COUNTERS
+            .iter()
+            .map(|(manager, count)| {
+                let name = format!("count_{}", manager);
+                let key = format!("packages.{}", manager);
+                let count = move || cache::cached(&key, &sources(manager), count);
+                (manager, scope.spawn(move || profile::time(&name, count)))
+            })
+            .collect();
 
-    if let Some(count) = count_go() {
-        counts.push(PackageCount {
-            manager: "go".to_string(),
-            count,
-        });
-    }
+        handles
+            .into_iter()
+            .filter_map(|(manager, handle)| {
+                let count = handle.join().ok().flatten()?;
+                Some(PackageCount {
+                    manager: manager.to_string(),
+                    count,
+                })
+            })
+            .collect()
+    });
 
     // Calculate total and format string
     let total: u32 = counts.iter().map(|c| c.count).sum();
@@ -149,10 +73,91 @@ pub fn gather(info: &mut SystemInfo) {
     }
 }
 
+// Files or directories that change when packages are installed or removed.
+// A directory's mtime only changes when an entry directly inside it is added
+// or removed, so these are the directories holding one entry per package.
+fn sources(manager: &str) -> Vec<PathBuf> {
+    let home = |path: &str| dirs::home_dir().map(|h| h.join(path));
+    let paths = match manager {
+        "pacman" => vec![Some(PathBuf::from("/var/lib/pacman/local"))],
+        "apt" => vec![Some(PathBuf::from("/var/lib/dpkg/status"))],
+        "dnf" | "rpm" | "zypper" => ["/var/lib/rpm", "/usr/lib/sysimage/rpm"]
+            .iter()
+            .flat_map(|dir| ["rpmdb.sqlite", "Packages", "Packages.db"].map(|db| Some(Path::new(dir).join(db))))
+            .collect(),
+        // New categories show up in the top directory, new packages in their category
+        "emerge" => std::iter::once(PathBuf::from("/var/db/pkg"))
+            .chain(subdirs(Path::new("/var/db/pkg"), "", ""))
+            .map(Some)
+            .collect(),
+        "xbps" => vec![Some(PathBuf::from("/var/db/xbps"))],
+        "apk" => vec![Some(PathBuf::from("/lib/apk/db/installed"))],
+        "nix" => vec![home(".nix-profile/manifest.nix")],
+        "guix" => vec![home(".guix-profile")],
+        "flatpak" => vec![
+            Some(PathBuf::from("/var/lib/flatpak/app")),
+            home(".local/share/flatpak/app"),
+        ],
+        "snap" => vec![Some(PathBuf::from("/var/lib/snapd/snaps"))],
+        "brew" => vec![
+            Some(PathBuf::from("/home/linuxbrew/.linuxbrew/Cellar")),
+            Some(PathBuf::from("/usr/local/Cellar")),
+        ],
+        "cargo" => vec![home(".cargo/bin")],
+        "pip" => ["/usr/lib", "/usr/local/lib"]
+            .iter()
+            .map(PathBuf::from)
+            .chain(home(".local/lib"))
+            .flat_map(|lib| {
+                ["site-packages", "dist-packages"]
+                    .into_iter()
+                    .flat_map(move |packages| subdirs(&lib, "python3", packages))
+            })
+            .map(Some)
+            .collect(),
+        "npm" => vec![
+            Some(PathBuf::from("/usr/lib/node_modules")),
+            Some(PathBuf::from("/usr/local/lib/node_modules")),
+            home(".npm-global/lib/node_modules"),
+        ],
+        "gem" => ["/var/lib/gems", "/usr/lib/ruby/gems", "/usr/local/lib/ruby/gems"]
+            .iter()
+            .map(PathBuf::from)
+            .chain(home(".local/share/gem/ruby"))
+            .chain(home(".gem/ruby"))
+            .flat_map(|gems| subdirs(&gems, "", "specifications"))
+            .map(Some)
+            .collect(),
+        "go" => vec![home("go/bin")],
+        _ => Vec::new(),
+    };
+    paths.into_iter().flatten().collect()
+}
+
+// `<dir>/<name starting with prefix>/<tail>` for every such directory, like
+// the versioned python3.12/site-packages
+fn subdirs(dir: &Path, prefix: &str, tail: &str) -> Vec<PathBuf> {
+    let Ok(entries) = sysroot::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(prefix) && entry.path().is_dir())
+        .map(|entry| {
+            let path = dir.join(entry.file_name());
+            if tail.is_empty() {
+                path
+            } else {
+                path.join(tail)
+            }
+        })
+        .collect()
+}
+
 fn count_pacman() -> Option<u32> {
     let db_path = Path::new("/var/lib/pacman/local");
-    if db_path.exists() {
-        fs::read_dir(db_path)
+    if sysroot::exists(db_path) {
+        sysroot::read_dir(db_path)
             .ok()
             .map(|entries| entries.filter_map(|e| e.ok()).count() as u32 - 1) // -1 for ALPM_DB_VERSION
     } else {
@@ -162,8 +167,8 @@ fn count_pacman() -> Option<u32> {
 
 fn count_apt() -> Option<u32> {
     let dpkg_path = Path::new("/var/lib/dpkg/status");
-    if dpkg_path.exists() {
-        if let Ok(content) = fs::read_to_string(dpkg_path) {
+    if sysroot::exists(dpkg_path) {
+        if let Ok(content) = sysroot::read_to_string(dpkg_path) {
             let count = content
                 .split("\n\n")
                 .filter(|pkg| {
@@ -179,15 +184,15 @@ fn count_apt() -> Option<u32> {
 
 fn count_dnf() -> Option<u32> {
     // DNF uses RPM database, so we use rpm command
-    if !Path::new("/usr/bin/dnf").exists() {
+    if !sysroot::exists("/usr/bin/dnf") {
         return None;
     }
     count_rpm()
 }
 
 fn count_rpm() -> Option<u32> {
-    if let Ok(output) = Command::new("rpm").args(["-qa", "--last"]).output() {
-        if output.status.success() {
+    if let Some(output) = command::run("rpm", &["-qa", "--last"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             return Some(content.lines().count() as u32);
         }
@@ -197,12 +202,12 @@ fn count_rpm() -> Option<u32> {
 
 fn count_emerge() -> Option<u32> {
     let db_path = Path::new("/var/db/pkg");
-    if db_path.exists() {
+    if sysroot::exists(db_path) {
         let mut count = 0u32;
-        if let Ok(categories) = fs::read_dir(db_path) {
+        if let Ok(categories) = sysroot::read_dir(db_path) {
             for cat in categories.flatten() {
                 if cat.path().is_dir() {
-                    if let Ok(packages) = fs::read_dir(cat.path()) {
+                    if let Ok(packages) = sysroot::read_dir(db_path.join(cat.file_name())) {
                         count += packages.filter_map(|e| e.ok()).count() as u32;
                     }
                 }
@@ -216,8 +221,8 @@ fn count_emerge() -> Option<u32> {
 }
 
 fn count_xbps() -> Option<u32> {
-    if let Ok(output) = Command::new("xbps-query").arg("-l").output() {
-        if output.status.success() {
+    if let Some(output) = command::run("xbps-query", &["-l"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             return Some(content.lines().count() as u32);
         }
@@ -226,8 +231,8 @@ fn count_xbps() -> Option<u32> {
 }
 
 fn count_apk() -> Option<u32> {
-    if let Ok(output) = Command::new("apk").args(["info"]).output() {
-        if output.status.success() {
+    if let Some(output) = command::run("apk", &["info"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             return Some(content.lines().count() as u32);
         }
@@ -236,8 +241,8 @@ fn count_apk() -> Option<u32> {
 }
 
 fn count_zypper() -> Option<u32> {
-    if let Ok(output) = Command::new("rpm").args(["-qa"]).output() {
-        if output.status.success() && Path::new("/usr/bin/zypper").exists() {
+    if let Some(output) = command::run("rpm", &["-qa"]) {
+        if output.success() && sysroot::exists("/usr/bin/zypper") {
             let content = String::from_utf8_lossy(&output.stdout);
             return Some(content.lines().count() as u32);
         }
@@ -249,9 +254,9 @@ fn count_nix() -> Option<u32> {
     // Count installed packages in user profile
     if let Some(home) = dirs::home_dir() {
         let nix_profile = home.join(".nix-profile/manifest.nix");
-        if nix_profile.exists() {
-            if let Ok(output) = Command::new("nix-env").args(["-q"]).output() {
-                if output.status.success() {
+        if sysroot::exists(&nix_profile) {
+            if let Some(output) = command::run("nix-env", &["-q"]) {
+                if output.success() {
                     let content = String::from_utf8_lossy(&output.stdout);
                     return Some(content.lines().count() as u32);
                 }
@@ -262,8 +267,8 @@ fn count_nix() -> Option<u32> {
 }
 
 fn count_guix() -> Option<u32> {
-    if let Ok(output) = Command::new("guix").args(["package", "-I"]).output() {
-        if output.status.success() {
+    if let Some(output) = command::run("guix", &["package", "-I"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             return Some(content.lines().count() as u32);
         }
@@ -272,11 +277,9 @@ fn count_guix() -> Option<u32> {
 }
 
 fn count_flatpak() -> Option<u32> {
-    if let Ok(output) = Command::new("flatpak")
-        .args(["list", "--app"])
-        .output()
+    if let Some(output) = command::run("flatpak", &["list", "--app"])
     {
-        if output.status.success() {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             let count = content.lines().count() as u32;
             if count > 0 {
@@ -288,8 +291,8 @@ fn count_flatpak() -> Option<u32> {
 }
 
 fn count_snap() -> Option<u32> {
-    if let Ok(output) = Command::new("snap").arg("list").output() {
-        if output.status.success() {
+    if let Some(output) = command::run("snap", &["list"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             // Subtract 1 for header line
             let count = content.lines().count().saturating_sub(1) as u32;
@@ -304,16 +307,16 @@ fn count_snap() -> Option<u32> {
 fn count_brew() -> Option<u32> {
     // Check for Homebrew on Linux (Linuxbrew)
     let brew_path = Path::new("/home/linuxbrew/.linuxbrew/bin/brew");
-    let brew_cmd = if brew_path.exists() {
+    let brew_cmd = if sysroot::exists(brew_path) {
         brew_path.to_string_lossy().to_string()
-    } else if Path::new("/usr/local/bin/brew").exists() {
+    } else if sysroot::exists("/usr/local/bin/brew") {
         "/usr/local/bin/brew".to_string()
     } else {
         return None;
     };
 
-    if let Ok(output) = Command::new(&brew_cmd).args(["list", "--formula", "-1"]).output() {
-        if output.status.success() {
+    if let Some(output) = command::run(&brew_cmd, &["list", "--formula", "-1"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             let count = content.lines().count() as u32;
             if count > 0 {
@@ -327,8 +330,8 @@ fn count_brew() -> Option<u32> {
 fn count_cargo() -> Option<u32> {
     if let Some(home) = dirs::home_dir() {
         let cargo_bin = home.join(".cargo/bin");
-        if cargo_bin.exists() {
-            if let Ok(entries) = fs::read_dir(&cargo_bin) {
+        if sysroot::exists(&cargo_bin) {
+            if let Ok(entries) = sysroot::read_dir(&cargo_bin) {
                 let count = entries
                     .filter_map(|e| e.ok())
                     .filter(|e| {
@@ -348,8 +351,8 @@ fn count_cargo() -> Option<u32> {
 
 fn count_pip() -> Option<u32> {
     // Count packages in pip --user or virtualenv
-    if let Ok(output) = Command::new("pip").args(["list", "--format=freeze"]).output() {
-        if output.status.success() {
+    if let Some(output) = command::run("pip", &["list", "--format=freeze"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             let count = content.lines().count() as u32;
             if count > 0 {
@@ -361,8 +364,8 @@ fn count_pip() -> Option<u32> {
 }
 
 fn count_npm() -> Option<u32> {
-    if let Ok(output) = Command::new("npm").args(["list", "-g", "--depth=0"]).output() {
-        if output.status.success() {
+    if let Some(output) = command::run("npm", &["list", "-g", "--depth=0"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             // Subtract 1 for the first line which shows the path
             let count = content.lines().count().saturating_sub(1) as u32;
@@ -375,8 +378,8 @@ fn count_npm() -> Option<u32> {
 }
 
 fn count_gem() -> Option<u32> {
-    if let Ok(output) = Command::new("gem").args(["list", "--no-details"]).output() {
-        if output.status.success() {
+    if let Some(output) = command::run("gem", &["list", "--no-details"]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             let count = content.lines().count() as u32;
             if count > 0 {
@@ -390,8 +393,8 @@ fn count_gem() -> Option<u32> {
 fn count_go() -> Option<u32> {
     if let Some(home) = dirs::home_dir() {
         let go_bin = home.join("go/bin");
-        if go_bin.exists() {
-            if let Ok(entries) = fs::read_dir(&go_bin) {
+        if sysroot::exists(&go_bin) {
+            if let Ok(entries) = sysroot::read_dir(&go_bin) {
                 let count = entries.filter_map(|e| e.ok()).count() as u32;
                 if count > 0 {
                     return Some(count);
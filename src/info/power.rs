@@ -1,10 +1,14 @@
-use crate::info::{BatteryInfo, SystemInfo};
-use std::fs;
+use crate::config::InfoOptions;
+use crate::info::{profile, sysroot, BatteryInfo, SystemInfo};
 use std::path::Path;
 
-pub fn gather(info: &mut SystemInfo) {
-    gather_battery(info);
-    gather_brightness(info);
+pub fn gather(info: &mut SystemInfo, options: &InfoOptions) {
+    if options.battery {
+        profile::time("gather_battery", || gather_battery(info));
+    }
+    if options.brightness {
+        profile::time("gather_brightness", || gather_brightness(info));
+    }
 }
 
 fn gather_battery(info: &mut SystemInfo) {
@@ -15,7 +19,7 @@ fn gather_battery(info: &mut SystemInfo) {
 
     let power_supply_path = Path::new("/sys/class/power_supply");
 
-    if !power_supply_path.exists() {
+    if !sysroot::exists(power_supply_path) {
         return;
     }
 
@@ -27,10 +31,10 @@ fn gather_battery(info: &mut SystemInfo) {
     let mut percents: Vec<u8> = Vec::new();
     let mut statuses: Vec<String> = Vec::new();
 
-    if let Ok(entries) = fs::read_dir(power_supply_path) {
+    if let Ok(entries) = sysroot::read_dir(power_supply_path) {
         for entry in entries.flatten() {
-            let path = entry.path();
             let name = entry.file_name().to_string_lossy().to_string();
+            let path = power_supply_path.join(&name);
 
             // Look for batteries (BAT0, BAT1, etc.)
             if !name.starts_with("BAT") && !name.contains("battery") {
@@ -39,7 +43,7 @@ fn gather_battery(info: &mut SystemInfo) {
 
             // Check if this is actually a battery
             let type_path = path.join("type");
-            if let Ok(device_type) = fs::read_to_string(&type_path) {
+            if let Ok(device_type) = sysroot::read_to_string(&type_path) {
                 if device_type.trim() != "Battery" {
                     continue;
                 }
@@ -51,7 +55,7 @@ fn gather_battery(info: &mut SystemInfo) {
             statuses.push(status.clone());
 
             // Get capacity (percentage)
-            if let Some(capacity) = fs::read_to_string(path.join("capacity"))
+            if let Some(capacity) = sysroot::read_to_string(path.join("capacity"))
                 .ok()
                 .and_then(|s| s.trim().parse::<u8>().ok())
             {
@@ -96,7 +100,7 @@ fn gather_battery(info: &mut SystemInfo) {
     }
 
     let status = resolve_status(&statuses);
-    let time_remaining = if has_energy && has_power && total_power_now > 0 {
+    let time_remaining_minutes = if has_energy && has_power && total_power_now > 0 {
         calculate_time_remaining_total(total_energy_now, total_energy_full, total_power_now, &status)
     } else {
         None
@@ -105,7 +109,7 @@ fn gather_battery(info: &mut SystemInfo) {
     info.battery = Some(BatteryInfo {
         percent,
         status: format_battery_status(&status),
-        time_remaining,
+        time_remaining_minutes,
     });
 }
 
@@ -114,7 +118,7 @@ fn calculate_time_remaining_total(
     energy_full: i64,
     power_now: i64,
     status: &str,
-) -> Option<String> {
+) -> Option<u32> {
     if power_now <= 0 {
         return None;
     }
@@ -126,10 +130,7 @@ fn calculate_time_remaining_total(
     };
 
     if hours > 0.0 && hours < 100.0 {
-        let total_minutes = (hours * 60.0) as u32;
-        let h = total_minutes / 60;
-        let m = total_minutes % 60;
-        return Some(format!("{}:{:02}", h, m));
+        return Some((hours * 60.0) as u32);
     }
 
     None
@@ -153,10 +154,10 @@ fn resolve_status(statuses: &[String]) -> String {
 
 #[cfg(target_os = "macos")]
 fn gather_macos_battery(info: &mut SystemInfo) -> bool {
-    use std::process::Command;
+    use crate::info::command;
 
-    let output = match Command::new("pmset").args(["-g", "batt"]).output() {
-        Ok(out) if out.status.success() => out,
+    let output = match command::run("pmset", &["-g", "batt"]) {
+        Some(out) if out.success() => out,
         _ => return false,
     };
 
@@ -182,21 +183,18 @@ fn gather_macos_battery(info: &mut SystemInfo) -> bool {
             "Unknown".to_string()
         };
 
-        let time_remaining = line.split(';').nth(2).and_then(|s| {
-            let trimmed = s.trim();
-            let time = trimmed.split_whitespace().next().unwrap_or("");
-            if time.contains(':') {
-                Some(time.to_string())
-            } else {
-                None
-            }
+        // "1:23 remaining", the time is absent while pmset is estimating
+        let time_remaining_minutes = line.split(';').nth(2).and_then(|s| {
+            let time = s.split_whitespace().next()?;
+            let (hours, minutes) = time.split_once(':')?;
+            Some(hours.parse::<u32>().ok()? * 60 + minutes.parse::<u32>().ok()?)
         });
 
         if let Some(percent) = percent {
             info.battery = Some(BatteryInfo {
                 percent,
                 status,
-                time_remaining,
+                time_remaining_minutes,
             });
             return true;
         }
@@ -206,7 +204,7 @@ fn gather_macos_battery(info: &mut SystemInfo) -> bool {
 }
 
 fn read_trimmed(path: &Path) -> Option<String> {
-    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+    sysroot::read_to_string(path).ok().map(|s| s.trim().to_string())
 }
 
 fn read_i64(path: &Path) -> Option<i64> {
@@ -226,26 +224,25 @@ fn format_battery_status(status: &str) -> String {
 fn gather_brightness(info: &mut SystemInfo) {
     let backlight_path = Path::new("/sys/class/backlight");
 
-    if !backlight_path.exists() {
+    if !sysroot::exists(backlight_path) {
         return;
     }
 
-    if let Ok(entries) = fs::read_dir(backlight_path) {
+    if let Ok(entries) = sysroot::read_dir(backlight_path) {
         for entry in entries.flatten() {
-            let path = entry.path();
+            let path = backlight_path.join(entry.file_name());
 
-            let brightness = fs::read_to_string(path.join("brightness"))
+            let brightness = sysroot::read_to_string(path.join("brightness"))
                 .ok()
                 .and_then(|s| s.trim().parse::<u32>().ok());
 
-            let max_brightness = fs::read_to_string(path.join("max_brightness"))
+            let max_brightness = sysroot::read_to_string(path.join("max_brightness"))
                 .ok()
                 .and_then(|s| s.trim().parse::<u32>().ok());
 
             if let (Some(current), Some(max)) = (brightness, max_brightness) {
                 if max > 0 {
-                    let percent = (current as f64 / max as f64 * 100.0) as u32;
-                    info.brightness = Some(format!("{}%", percent));
+                    info.brightness_percent = Some((current as f64 / max as f64 * 100.0) as u32);
                     break;
                 }
             }
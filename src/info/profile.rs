@@ -0,0 +1,122 @@
+// Timing of gather functions and external commands for `--profile`.
+//
+// Recording is off unless enabled, in which case every `time` call and
+// every command run adds a sample. Probes run concurrently, so the samples
+// add up to more than the total wall time.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Probe,
+    Command,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Timing {
+    pub name: String,
+    pub kind: Kind,
+    pub ms: f64,
+    // Commands that were killed or skipped because of the time budget
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub total_ms: f64,
+    pub timings: Vec<Timing>,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TIMINGS: Mutex<Vec<Timing>> = Mutex::new(Vec::new());
+static STARTED: Mutex<Option<Instant>> = Mutex::new(None);
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Clears previous samples and starts the total clock.
+pub fn start() {
+    if !enabled() {
+        return;
+    }
+    TIMINGS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    *STARTED.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+}
+
+/// Runs `f`, recording how long it took under `name`.
+pub fn time<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    if !enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    record(name, Kind::Probe, start.elapsed(), false);
+    result
+}
+
+pub fn record(name: &str, kind: Kind, duration: Duration, timed_out: bool) {
+    if !enabled() {
+        return;
+    }
+    TIMINGS.lock().unwrap_or_else(|e| e.into_inner()).push(Timing {
+        name: name.to_string(),
+        kind,
+        ms: duration.as_secs_f64() * 1000.0,
+        timed_out,
+    });
+}
+
+/// Samples collected since `start`, slowest first.
+pub fn report() -> Report {
+    let total = STARTED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .map(|start| start.elapsed())
+        .unwrap_or_default();
+
+    let mut timings = TIMINGS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    timings.sort_by(|a, b| b.ms.total_cmp(&a.ms));
+
+    Report {
+        total_ms: total.as_secs_f64() * 1000.0,
+        timings,
+    }
+}
+
+pub fn print_report(report: &Report) {
+    let width = report
+        .timings
+        .iter()
+        .map(|t| t.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("total".len());
+
+    eprintln!("{:<width$}  {:<7}  {:>10}", "name", "kind", "time", width = width);
+    for timing in &report.timings {
+        let kind = match timing.kind {
+            Kind::Probe => "probe",
+            Kind::Command => "command",
+        };
+        let note = if timing.timed_out { "  (timed out)" } else { "" };
+        eprintln!(
+            "{:<width$}  {:<7}  {:>8.2}ms{}",
+            timing.name,
+            kind,
+            timing.ms,
+            note,
+            width = width
+        );
+    }
+    eprintln!("{:<width$}  {:<7}  {:>8.2}ms", "total", "", report.total_ms, width = width);
+}
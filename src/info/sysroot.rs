@@ -0,0 +1,88 @@
+// Filesystem root for everything the info modules read.
+//
+// By default this is `/`. With `HYPERFETCH_ROOT` or `--sysroot` every
+// absolute path is looked up below the given directory instead, so
+// hyperfetch can inspect captured fixture trees, container rootfs
+// snapshots or chroots. The helpers take logical paths on the target
+// system and always prefix them. Mapped paths come back as `HostPath`, which
+// the helpers do not accept, so nothing is mapped twice.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+static ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Sets the root, `None` or `/` reads the live system.
+pub fn set(root: Option<PathBuf>) {
+    let root = root.filter(|r| r != Path::new("/"));
+    *ROOT.write().unwrap_or_else(|e| e.into_inner()) = root;
+}
+
+pub fn root() -> Option<PathBuf> {
+    ROOT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub fn is_set() -> bool {
+    root().is_some()
+}
+
+/// A path on the machine running hyperfetch, already below the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostPath(PathBuf);
+
+impl HostPath {
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.0.is_dir()
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.0.is_file()
+    }
+}
+
+/// An entry of `read_dir`, build paths below it from `file_name`.
+pub struct DirEntry(fs::DirEntry);
+
+impl DirEntry {
+    pub fn file_name(&self) -> OsString {
+        self.0.file_name()
+    }
+
+    pub fn path(&self) -> HostPath {
+        HostPath(self.0.path())
+    }
+}
+
+/// Maps an absolute path on the target system to the path to read.
+pub fn path<P: AsRef<Path>>(path: P) -> HostPath {
+    let path = path.as_ref();
+    match root() {
+        Some(root) => HostPath(root.join(path.strip_prefix("/").unwrap_or(path))),
+        None => HostPath(path.to_path_buf()),
+    }
+}
+
+pub fn read_to_string<P: AsRef<Path>>(p: P) -> io::Result<String> {
+    fs::read_to_string(path(p).0)
+}
+
+pub fn read_dir<P: AsRef<Path>>(p: P) -> io::Result<impl Iterator<Item = io::Result<DirEntry>>> {
+    Ok(fs::read_dir(path(p).0)?.map(|entry| entry.map(DirEntry)))
+}
+
+// The link target is returned as stored, absolute targets refer to the
+// target system and can be passed to the other helpers again
+pub fn read_link<P: AsRef<Path>>(p: P) -> io::Result<PathBuf> {
+    fs::read_link(path(p).0)
+}
+
+pub fn exists<P: AsRef<Path>>(p: P) -> bool {
+    path(p).0.exists()
+}
@@ -1,40 +1,59 @@
-use crate::info::SystemInfo;
-use std::fs;
+use crate::config::InfoOptions;
+use crate::info::{command, profile, sysroot, SystemInfo};
 use std::path::Path;
-use std::process::Command;
-use sysinfo::System;
-
-pub fn gather(info: &mut SystemInfo) {
-    gather_os(info);
-    gather_kernel(info);
-    gather_hostname(info);
-    gather_uptime(info);
-    gather_load_average(info);
-    gather_processes(info);
-    gather_users(info);
-    gather_machine_type(info);
-    gather_init_system(info);
-    gather_boot_time(info);
+use sysinfo::{System, ThreadKind};
+
+pub fn gather(info: &mut SystemInfo, options: &InfoOptions) {
+    // The logo follows the distro, whether or not the OS line is shown
+    profile::time("gather_os_id", || gather_os_id(info));
+    if options.os {
+        profile::time("gather_os", || gather_os(info));
+    }
+    if options.kernel {
+        profile::time("gather_kernel", || gather_kernel(info));
+    }
+    if options.hostname {
+        profile::time("gather_hostname", || gather_hostname(info));
+    }
+    if options.uptime {
+        profile::time("gather_uptime", || gather_uptime(info));
+    }
+    if options.load_average {
+        profile::time("gather_load_average", || gather_load_average(info));
+    }
+    if options.processes {
+        profile::time("gather_processes", || gather_processes(info));
+    }
+    if options.logged_users {
+        profile::time("gather_users", || gather_users(info));
+    }
+    if options.machine_type {
+        profile::time("gather_machine_type", || gather_machine_type(info));
+    }
+    if options.init_system {
+        profile::time("gather_init_system", || gather_init_system(info));
+    }
+    if options.boot_time {
+        profile::time("gather_boot_time", || gather_boot_time(info));
+    }
 }
 
 fn gather_os(info: &mut SystemInfo) {
     // Try /etc/os-release first
-    if let Ok(content) = fs::read_to_string("/etc/os-release") {
-        let (name, version, id) = parse_os_release(&content);
+    if let Ok(content) = sysroot::read_to_string("/etc/os-release") {
+        let (name, version, _) = parse_os_release(&content);
 
         if let Some(n) = name {
             info.os = Some(n);
         } else if let Some(v) = version {
             info.os = Some(format!("Linux {}", v));
         }
-
-        info.os_id = id;
     }
 
     // Fallback to lsb_release
     if info.os.is_none() {
-        if let Ok(output) = Command::new("lsb_release").arg("-ds").output() {
-            if output.status.success() {
+        if let Some(output) = command::run("lsb_release", &["-ds"]) {
+            if output.success() {
                 let s = String::from_utf8_lossy(&output.stdout)
                     .trim()
                     .trim_matches('"')
@@ -60,7 +79,6 @@ fn gather_os(info: &mut SystemInfo) {
                 (None, None) => "macOS".to_string(),
             };
             info.os = Some(os_name);
-            info.os_id = Some("macos".to_string());
         }
     }
 
@@ -70,16 +88,27 @@ fn gather_os(info: &mut SystemInfo) {
     }
 }
 
+fn gather_os_id(info: &mut SystemInfo) {
+    if let Ok(content) = sysroot::read_to_string("/etc/os-release") {
+        info.os_id = parse_os_release(&content).2;
+    }
+
+    #[cfg(target_os = "macos")]
+    if info.os_id.is_none() {
+        info.os_id = Some("macos".to_string());
+    }
+}
+
 fn gather_kernel(info: &mut SystemInfo) {
-    if let Ok(content) = fs::read_to_string("/proc/version") {
+    if let Ok(content) = sysroot::read_to_string("/proc/version") {
         if let Some(version) = content.split_whitespace().nth(2) {
             info.kernel = Some(version.to_string());
         }
     }
 
     if info.kernel.is_none() {
-        if let Ok(output) = Command::new("uname").arg("-r").output() {
-            if output.status.success() {
+        if let Some(output) = command::run("uname", &["-r"]) {
+            if output.success() {
                 info.kernel = Some(trim_lossy(&output.stdout));
             }
         }
@@ -87,12 +116,15 @@ fn gather_kernel(info: &mut SystemInfo) {
 }
 
 fn gather_hostname(info: &mut SystemInfo) {
-    if let Ok(name) = hostname::get() {
-        info.hostname = Some(name.to_string_lossy().to_string());
+    // The kernel hostname belongs to the live system, not to a sysroot
+    if !sysroot::is_set() {
+        if let Ok(name) = hostname::get() {
+            info.hostname = Some(name.to_string_lossy().to_string());
+        }
     }
 
     // Try to get FQDN
-    if let Ok(content) = fs::read_to_string("/etc/hostname") {
+    if let Ok(content) = sysroot::read_to_string("/etc/hostname") {
         let h = content.trim().to_string();
         if !h.is_empty() && info.hostname.is_none() {
             info.hostname = Some(h);
@@ -101,60 +133,87 @@ fn gather_hostname(info: &mut SystemInfo) {
 }
 
 fn gather_uptime(info: &mut SystemInfo) {
-    if let Ok(content) = fs::read_to_string("/proc/uptime") {
+    if let Ok(content) = sysroot::read_to_string("/proc/uptime") {
         if let Some(seconds_str) = content.split_whitespace().next() {
             if let Ok(seconds) = seconds_str.parse::<f64>() {
                 let secs = seconds as u64;
                 info.uptime_seconds = Some(secs);
-                info.uptime = Some(format_uptime(secs));
             }
         }
     }
 
-    if info.uptime.is_none() {
+    if info.uptime_seconds.is_none() {
         #[cfg(target_os = "macos")]
         if let Some(boot_time) = macos_boot_time_seconds() {
             if let Ok(now) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
                 let secs = now.as_secs().saturating_sub(boot_time as u64);
                 info.uptime_seconds = Some(secs);
-                info.uptime = Some(format_uptime(secs));
             }
         }
     }
 }
 
 fn gather_load_average(info: &mut SystemInfo) {
-    if let Ok(content) = fs::read_to_string("/proc/loadavg") {
-        let parts: Vec<&str> = content.split_whitespace().collect();
-        if parts.len() >= 3 {
-            info.load_average = Some(format!("{}, {}, {}", parts[0], parts[1], parts[2]));
-        }
+    if let Ok(content) = sysroot::read_to_string("/proc/loadavg") {
+        info.load_average = parse_load(&content);
     }
 
     if info.load_average.is_none() {
         #[cfg(target_os = "macos")]
-        if let Ok(output) = Command::new("sysctl").args(["-n", "vm.loadavg"]).output() {
-            if output.status.success() {
+        if let Some(output) = command::run("sysctl", &["-n", "vm.loadavg"]) {
+            if output.success() {
                 let s = trim_lossy(&output.stdout);
                 let cleaned = s.trim_matches('{').trim_matches('}').trim();
-                let parts: Vec<&str> = cleaned.split_whitespace().collect();
-                if parts.len() >= 3 {
-                    info.load_average = Some(format!("{}, {}, {}", parts[0], parts[1], parts[2]));
-                }
+                info.load_average = parse_load(cleaned);
             }
         }
     }
 }
 
+// First three whitespace separated numbers
+fn parse_load(content: &str) -> Option<[f64; 3]> {
+    let mut values = content.split_whitespace().map(|v| v.parse::<f64>().ok());
+    Some([values.next()??, values.next()??, values.next()??])
+}
+
 fn gather_processes(info: &mut SystemInfo) {
+    if sysroot::is_set() {
+        info.processes = count_sysroot_tasks();
+        return;
+    }
+
     let sys = System::new_all();
-    info.processes = Some(sys.processes().len() as u32);
+    let own_pid = sysinfo::get_current_pid().ok();
+
+    // Leave out our own gather threads so running modules concurrently
+    // doesn't inflate the count
+    let count = sys
+        .processes()
+        .values()
+        .filter(|p| !(p.thread_kind() == Some(ThreadKind::Userland) && p.parent() == own_pid))
+        .count();
+    info.processes = Some(count as u32);
+}
+
+// Same count as sysinfo gives on the live system: every task of every process
+fn count_sysroot_tasks() -> Option<u32> {
+    let count = sysroot::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .map(|entry| {
+            sysroot::read_dir(Path::new("/proc").join(entry.file_name()).join("task"))
+                .map(|tasks| tasks.count())
+                .unwrap_or(1)
+        })
+        .sum::<usize>();
+    Some(count as u32)
 }
 
 fn gather_users(info: &mut SystemInfo) {
     // Count unique logged-in users from /var/run/utmp or use `who`
-    if let Ok(output) = Command::new("who").output() {
-        if output.status.success() {
+    if let Some(output) = command::run("who", &[]) {
+        if output.success() {
             let content = String::from_utf8_lossy(&output.stdout);
             let users: Vec<&str> = content.lines().filter_map(|l| l.split_whitespace().next()).collect();
             let unique: std::collections::HashSet<_> = users.into_iter().collect();
@@ -168,12 +227,12 @@ fn gather_users(info: &mut SystemInfo) {
 
 fn gather_machine_type(info: &mut SystemInfo) {
     // Check for VM/Container first
-    if Path::new("/.dockerenv").exists() {
+    if sysroot::exists("/.dockerenv") {
         info.machine_type = Some("Container (Docker)".to_string());
         return;
     }
 
-    if let Ok(content) = fs::read_to_string("/proc/1/cgroup") {
+    if let Ok(content) = sysroot::read_to_string("/proc/1/cgroup") {
         if content.contains("docker") || content.contains("lxc") || content.contains("kubepods") {
             info.machine_type = Some("Container".to_string());
             return;
@@ -181,7 +240,7 @@ fn gather_machine_type(info: &mut SystemInfo) {
     }
 
     // Check DMI for VM
-    if let Ok(content) = fs::read_to_string("/sys/class/dmi/id/product_name") {
+    if let Ok(content) = sysroot::read_to_string("/sys/class/dmi/id/product_name") {
         let product = content.trim().to_lowercase();
         if product.contains("virtualbox") {
             info.machine_type = Some("Virtual Machine (VirtualBox)".to_string());
@@ -199,7 +258,7 @@ fn gather_machine_type(info: &mut SystemInfo) {
     }
 
     // Check chassis type for physical machines
-    if let Ok(content) = fs::read_to_string("/sys/class/dmi/id/chassis_type") {
+    if let Ok(content) = sysroot::read_to_string("/sys/class/dmi/id/chassis_type") {
         let chassis: u32 = content.trim().parse().unwrap_or(0);
         info.machine_type = Some(
             match chassis {
@@ -215,7 +274,7 @@ fn gather_machine_type(info: &mut SystemInfo) {
 }
 
 fn gather_init_system(info: &mut SystemInfo) {
-    if let Ok(target) = fs::read_link("/sbin/init") {
+    if let Ok(target) = sysroot::read_link("/sbin/init") {
         let target_str = target.to_string_lossy().to_lowercase();
         if target_str.contains("systemd") {
             info.init_system = Some("systemd".to_string());
@@ -233,21 +292,21 @@ fn gather_init_system(info: &mut SystemInfo) {
     }
 
     // Check by process
-    if Path::new("/run/systemd/system").exists() {
+    if sysroot::exists("/run/systemd/system") {
         info.init_system = Some("systemd".to_string());
-    } else if Path::new("/run/openrc").exists() {
+    } else if sysroot::exists("/run/openrc") {
         info.init_system = Some("OpenRC".to_string());
-    } else if Path::new("/run/runit").exists() || Path::new("/etc/runit").exists() {
+    } else if sysroot::exists("/run/runit") || sysroot::exists("/etc/runit") {
         info.init_system = Some("runit".to_string());
-    } else if Path::new("/run/s6").exists() {
+    } else if sysroot::exists("/run/s6") {
         info.init_system = Some("s6".to_string());
-    } else if Path::new("/etc/init.d").exists() {
+    } else if sysroot::exists("/etc/init.d") {
         info.init_system = Some("SysVinit".to_string());
     }
 }
 
 fn gather_boot_time(info: &mut SystemInfo) {
-    if let Ok(content) = fs::read_to_string("/proc/stat") {
+    if let Ok(content) = sysroot::read_to_string("/proc/stat") {
         for line in content.lines() {
             if line.starts_with("btime ") {
                 if let Some(ts) = line.split_whitespace().nth(1) {
@@ -281,7 +340,7 @@ fn gather_boot_time(info: &mut SystemInfo) {
     }
 }
 
-fn timestamp_to_datetime(timestamp: i64) -> (i32, u32, u32, u32, u32) {
+pub(crate) fn timestamp_to_datetime(timestamp: i64) -> (i32, u32, u32, u32, u32) {
     // Simple UTC conversion (good enough for display)
     let secs_per_day = 86400i64;
     let days = timestamp / secs_per_day;
@@ -327,25 +386,6 @@ fn is_leap_year(year: i32) -> bool {
     (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
 }
 
-fn format_uptime(secs: u64) -> String {
-    let days = secs / 86400;
-    let hours = (secs % 86400) / 3600;
-    let mins = (secs % 3600) / 60;
-
-    let mut parts = Vec::new();
-    if days > 0 {
-        parts.push(format!("{} day{}", days, if days == 1 { "" } else { "s" }));
-    }
-    if hours > 0 {
-        parts.push(format!("{} hour{}", hours, if hours == 1 { "" } else { "s" }));
-    }
-    if mins > 0 || parts.is_empty() {
-        parts.push(format!("{} min{}", mins, if mins == 1 { "" } else { "s" }));
-    }
-
-    parts.join(", ")
-}
-
 fn parse_os_release(content: &str) -> (Option<String>, Option<String>, Option<String>) {
     let mut name = None;
     let mut version = None;
@@ -374,8 +414,8 @@ fn trim_lossy(bytes: &[u8]) -> String {
 
 #[cfg(target_os = "macos")]
 fn command_output_trim(cmd: &str, args: &[&str]) -> Option<String> {
-    let output = Command::new(cmd).args(args).output().ok()?;
-    if !output.status.success() {
+    let output = command::run(cmd, args)?;
+    if !output.success() {
         return None;
     }
     let value = trim_lossy(&output.stdout);
@@ -388,8 +428,8 @@ fn command_output_trim(cmd: &str, args: &[&str]) -> Option<String> {
 
 #[cfg(target_os = "macos")]
 fn macos_boot_time_seconds() -> Option<i64> {
-    let output = Command::new("sysctl").args(["-n", "kern.boottime"]).output().ok()?;
-    if !output.status.success() {
+    let output = command::run("sysctl", &["-n", "kern.boottime"])?;
+    if !output.success() {
         return None;
     }
 
@@ -1,6 +1,11 @@
 pub mod ascii;
 pub mod config;
+pub mod diff;
 pub mod display;
+pub mod history;
 pub mod info;
+pub mod query;
+pub mod schema;
+pub mod serve;
 
 pub use config::Config;
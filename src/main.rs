@@ -1,15 +1,27 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use colored::control::set_override;
 
 mod ascii;
 mod config;
+mod diff;
 mod display;
+mod history;
 mod info;
+mod query;
+mod schema;
+mod serve;
 
 use ascii::AsciiArt;
-use config::Config;
-use display::{DisplayConfig, list_logos, print_info, print_json, print_logo_only};
+use config::{Config, InfoAlign, ThemeOptions};
+use display::output::{print_output, Format};
+use display::{DisplayConfig, LogoPosition, list_logos, print_info, print_logo_only};
+use display::theme::{self, Theme};
+use info::cache::Mode as CacheMode;
 use info::SystemInfo;
+use query::Query;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Hyperfetch - A comprehensive system information tool
 #[derive(Parser, Debug)]
@@ -20,7 +32,7 @@ use info::SystemInfo;
 #[command(long_about = None)]
 struct Args {
     /// Use custom config file
-    #[arg(short, long, value_name = "FILE")]
+    #[arg(short, long, value_name = "FILE", global = true)]
     config: Option<String>,
 
     /// Use specific distro's ASCII art
@@ -32,7 +44,7 @@ struct Args {
     no_ascii: bool,
 
     /// Disable colored output
-    #[arg(long)]
+    #[arg(long, global = true)]
     no_colors: bool,
 
     /// Use small ASCII art
@@ -43,18 +55,104 @@ struct Args {
     #[arg(short, long)]
     logo_only: bool,
 
-    /// Output as JSON
-    #[arg(short, long)]
+    /// Output as JSON (same as --format json)
+    #[arg(short, long, conflicts_with = "format")]
     json: bool,
 
+    /// Print machine-readable output instead of the logo and info lines
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    format: Option<Format>,
+
+    /// Print a single value, e.g. kernel, memory.used, disks[/].percent or gpu[0].driver
+    #[arg(long, value_name = "PATH", value_parser = Query::parse, conflicts_with_all = ["json", "format"])]
+    get: Option<Query>,
+
     /// Fetch and display public IP with geolocation
     #[cfg(feature = "network")]
     #[arg(long)]
     public_ip: bool,
 
+    /// Report how long each probe and external command took (on stderr,
+    /// or under "diagnostics" with --json/--format)
+    #[arg(long)]
+    profile: bool,
+
+    /// Show a snapshot saved with --json instead of this machine ("-" reads stdin)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["sysroot", "profile"])]
+    from_json: Option<PathBuf>,
+
+    /// Read system files below this directory instead of / (also HYPERFETCH_ROOT)
+    #[arg(long, value_name = "DIR")]
+    sysroot: Option<PathBuf>,
+
+    /// Do not read or write the probe cache
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Re-run cached probes and store the fresh results
+    #[arg(long, conflicts_with = "no_cache", global = true)]
+    refresh_cache: bool,
+
     /// List available ASCII logos
     #[arg(long)]
     list_logos: bool,
+
+    /// Put the logo left, right, above or below the info
+    #[arg(long, value_enum, value_name = "POSITION")]
+    logo_position: Option<LogoPosition>,
+
+    /// Theme for the info lines, a built-in name or a theme file
+    #[arg(long, value_name = "NAME")]
+    theme: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show which fields changed between two snapshots saved with --json,
+    /// or between a snapshot and this machine
+    Diff {
+        /// Older snapshot
+        old: PathBuf,
+
+        /// Newer snapshot, this machine when omitted
+        new: Option<PathBuf>,
+
+        /// Print the changes as JSON
+        #[arg(short, long)]
+        json: bool,
+
+        /// Include readings that change on every run (uptime, load, usage, temperatures)
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Show past fetches recorded with [history] enabled
+    History {
+        /// Number of recent records to list
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 10)]
+        recent: usize,
+
+        /// Print all records as JSON
+        #[arg(short, long)]
+        json: bool,
+    },
+
+    /// Print the JSON Schema of --json output
+    Schema,
+
+    /// Serve /json, /metrics and /text over HTTP
+    Serve {
+        /// Address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:9477")]
+        listen: SocketAddr,
+
+        /// Seconds before a request may trigger a new gather
+        #[arg(long, value_name = "SECS", default_value_t = 5)]
+        min_interval: u64,
+    },
 }
 
 fn main() {
@@ -66,25 +164,58 @@ fn main() {
         return;
     }
 
-    // Load configuration
-    let config = if let Some(ref path) = args.config {
-        match Config::load_from(path) {
-            Ok(c) => c,
+    if let Some(Command::Schema) = args.command {
+        match schema::schema() {
+            Ok(schema) => println!("{}", serde_json::to_string_pretty(&schema).unwrap_or_default()),
             Err(e) => {
-                eprintln!("Error loading config: {}", e);
-                Config::default()
+                eprintln!("Error building schema: {}", e);
+                std::process::exit(2);
             }
         }
-    } else {
-        Config::load()
+        return;
+    }
+
+    // Load configuration
+    let loaded = match args.config {
+        Some(ref path) => Config::load_from(path),
+        None => Config::load(),
+    };
+    let mut config = match loaded {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Error loading config: {}", e);
+            Config::default()
+        }
+    };
+    for warning in config.warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    // Resolve the theme once, so broken theme files are reported up front
+    if let Some(ref name) = args.theme {
+        config.theme.name = Some(name.clone());
+    }
+    config.theme = match theme::load(config.theme.clone()) {
+        Ok(theme) => theme,
+        Err(e) => {
+            eprintln!("Warning: {}, using the default theme", e);
+            theme::load(ThemeOptions::default()).unwrap_or_default()
+        }
     };
 
+    // Alternate filesystem root for fixture trees, rootfs snapshots and chroots
+    let sysroot = args
+        .sysroot
+        .clone()
+        .or_else(|| std::env::var_os("HYPERFETCH_ROOT").map(PathBuf::from))
+        .filter(|root| !root.as_os_str().is_empty());
+    info::sysroot::set(sysroot);
+
     // Handle colors
     let use_colors = !args.no_colors && config.display.show_colors;
     set_override(use_colors);
 
     // Determine ASCII settings
-    let show_ascii = !args.no_ascii && config.display.show_ascii;
     let small_ascii = args.small || config.display.small_ascii;
 
     // Determine distro for ASCII art
@@ -95,7 +226,7 @@ fn main() {
 
     // Handle --logo-only
     if args.logo_only {
-        let detected_distro = std::fs::read_to_string("/etc/os-release")
+        let detected_distro = info::sysroot::read_to_string("/etc/os-release")
             .ok()
             .and_then(|content| {
                 content
@@ -114,7 +245,10 @@ fn main() {
 
         let distro = ascii_distro.or(detected_distro.as_deref());
 
-        let ascii = AsciiArt::for_distro(distro, small_ascii);
+        let mut ascii = AsciiArt::for_distro(distro, small_ascii);
+        if let Some(colors) = config.logo_palette() {
+            ascii.colors = colors;
+        }
         let display_config = DisplayConfig {
             use_colors,
             ..Default::default()
@@ -123,26 +257,163 @@ fn main() {
         return;
     }
 
-    // Determine if we should fetch public IP
+    // Determine which modules to gather, --get only needs the ones on its path
+    #[allow(unused_mut)]
+    let mut info_options = match &args.get {
+        Some(query) => query.options().unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }),
+        None => config.info.clone(),
+    };
     #[cfg(feature = "network")]
-    let fetch_public_ip = args.public_ip || config.info.public_ip;
-    #[cfg(not(feature = "network"))]
-    let fetch_public_ip = false;
+    if args.public_ip {
+        info_options.public_ip = true;
+    }
+
+    // Limit how long external commands may take
+    info::command::configure(
+        Duration::from_millis(config.commands.timeout_ms),
+        Duration::from_millis(config.commands.budget_ms),
+    );
 
-    // Gather system information
-    let info = SystemInfo::gather(fetch_public_ip);
+    // Reuse slow probe results between runs
+    // Results from another root must not end up in this system's cache
+    let cache_mode = if args.no_cache || !config.cache.enabled || info::sysroot::is_set() {
+        CacheMode::Off
+    } else if args.refresh_cache {
+        CacheMode::Refresh
+    } else {
+        CacheMode::Normal
+    };
+    info::cache::configure(cache_mode, config.cache.ttl.clone());
+
+    if args.profile {
+        info::profile::enable();
+    }
 
-    // Handle --json output
-    if args.json {
-        print_json(&info);
+    if let Some(Command::History { recent, json }) = &args.command {
+        let records = history::load();
+        if *json || args.json {
+            println!("{}", serde_json::to_string_pretty(&records).unwrap_or_default());
+        } else {
+            history::print_summary(&records, *recent);
+        }
         return;
     }
 
+    if let Some(Command::Diff { old, new, json, all }) = &args.command {
+        let load = |path: &PathBuf| {
+            SystemInfo::load_from(path).unwrap_or_else(|e| {
+                eprintln!("Error loading snapshot: {}", e);
+                std::process::exit(2);
+            })
+        };
+        let old = load(old);
+        let new = match new {
+            Some(path) => load(path),
+            None => SystemInfo::gather(&info_options),
+        };
+
+        let changes = diff::diff(&old, &new, *all);
+        if *json || args.json {
+            println!("{}", serde_json::to_string_pretty(&changes).unwrap_or_default());
+        } else {
+            diff::print_text(&changes);
+        }
+        // Like diff(1): 1 when something changed
+        std::process::exit(if changes.is_empty() { 0 } else { 1 });
+    }
+
+    if let Some(Command::Serve { listen, min_interval }) = args.command {
+        // Responses are plain text, whatever the terminal settings
+        set_override(false);
+        let gather = || SystemInfo::gather(&info_options);
+        let text = |info: &SystemInfo| {
+            let (ascii, display_config) = display_setup(&args, &config, info, false);
+            let mut text = display::render_info(info, &ascii, &display_config).join("\n");
+            text.push('\n');
+            text
+        };
+        if let Err(e) = serve::run(listen, Duration::from_secs(min_interval), &gather, &text) {
+            eprintln!("Error serving on {}: {}", listen, e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Gather system information, or load a saved snapshot
+    let info = match &args.from_json {
+        Some(path) => match SystemInfo::load_from(path) {
+            Ok(info) => info,
+            Err(e) => {
+                eprintln!("Error loading snapshot: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => SystemInfo::gather(&info_options),
+    };
+    let report = args.profile.then(info::profile::report);
+
+    // Only full fetches of this machine go into the history
+    if config.history.enabled && args.get.is_none() && args.from_json.is_none() && !info::sysroot::is_set() {
+        history::record(&info, config.history.max_records);
+    }
+
+    // Handle --get
+    if let Some(query) = &args.get {
+        match query.get(&info) {
+            Some(value) => println!("{}", value),
+            None => {
+                eprintln!("No value for '{}'", query.path());
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Handle --json and --format output
+    if let Some(format) = args.format.or(args.json.then_some(Format::Json)) {
+        match format {
+            Format::Markdown | Format::Html | Format::Svg => {
+                if let Some(report) = &report {
+                    info::profile::print_report(report);
+                }
+                let (ascii, display_config) = display_setup(&args, &config, &info, use_colors);
+                let document = match format {
+                    Format::Html => display::document::html(&info, &ascii, &display_config),
+                    Format::Svg => display::svg::render(&info, &ascii, &display_config),
+                    _ => display::document::markdown(&info, &display_config),
+                };
+                print!("{}", document);
+            }
+            _ => print_output(&info, format, report.as_ref()),
+        }
+        return;
+    }
+
+    if let Some(report) = &report {
+        info::profile::print_report(report);
+    }
+
+    // Print everything, fitted to the terminal
+    let (ascii, mut display_config) = display_setup(&args, &config, &info, use_colors);
+    display_config.width = display::layout::terminal_width();
+    print_info(&info, &ascii, &display_config);
+}
+
+fn display_setup(args: &Args, config: &Config, info: &SystemInfo, use_colors: bool) -> (AsciiArt, DisplayConfig) {
     // Get ASCII art
-    let distro = ascii_distro
-        .map(|s| s.to_string())
+    let distro = args
+        .ascii
+        .clone()
+        .or_else(|| config.display.ascii_distro.clone())
         .or_else(|| info.os_id.clone());
-    let ascii = AsciiArt::for_distro(distro.as_deref(), small_ascii);
+    let small_ascii = args.small || config.display.small_ascii;
+    let mut ascii = AsciiArt::for_distro(distro.as_deref(), small_ascii);
+    if let Some(colors) = config.logo_palette() {
+        ascii.colors = colors;
+    }
 
     // Build display config
     let primary_color = if config.colors.primary.to_lowercase() == "auto"
@@ -152,13 +423,23 @@ fn main() {
     } else {
         config.primary_color()
     };
+    let secondary_color = config
+        .secondary_color()
+        .or_else(|| ascii.colors.get(1).or(ascii.colors.first()).copied());
 
     let display_config = DisplayConfig {
-        show_ascii,
+        show_ascii: !args.no_ascii && config.display.show_ascii,
         use_colors,
-        primary_color,
+        theme: Theme::new(&config.theme, primary_color, secondary_color),
+        modules: config.display.modules.clone(),
+        templates: config.templates.clone(),
+        bars: config.bars.clone(),
+        width: None,
+        logo_position: args.logo_position.unwrap_or(config.display.logo_position),
+        logo_gap: config.display.logo_gap,
+        info_offset: config.display.info_offset,
+        center_info: config.display.info_align == InfoAlign::Center,
     };
 
-    // Print everything
-    print_info(&info, &ascii, &display_config);
+    (ascii, display_config)
 }
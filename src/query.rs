@@ -0,0 +1,460 @@
+// Single-value lookups for `--get`.
+//
+// A path is a chain of field names separated by dots, each optionally
+// followed by selectors in brackets:
+//
+//   kernel              a top-level field
+//   memory.used         dotted names also match the underscore form, memory_used
+//   gpu[0].driver       list element by index
+//   disks[/].percent    list element by its mount, name or manager
+//
+// Paths are resolved against the JSON serialization of `SystemInfo`, with the
+// display text of the numeric fields (`memory`, `uptime`, ...) as a fallback.
+
+use crate::config::InfoOptions;
+use crate::display::modules::derived_fields;
+use crate::info::SystemInfo;
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Select(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Query {
+    path: String,
+    segments: Vec<Segment>,
+}
+
+/// Fields identifying an element of a list for `[name]` selectors.
+pub const ELEMENT_KEYS: &[&str] = &["mount", "name", "manager"];
+
+// Top-level field (or derived placeholder) to the `[info]` toggles that fill it
+const TOGGLES: &[(&str, &[&str])] = &[
+    ("os", &["os"]),
+    // Always gathered, for the logo
+    ("os_id", &[]),
+    ("kernel", &["kernel"]),
+    ("hostname", &["hostname"]),
+    ("uptime", &["uptime"]),
+    ("uptime_seconds", &["uptime"]),
+    ("load_average", &["load_average"]),
+    ("processes", &["processes"]),
+    ("logged_users", &["logged_users"]),
+    ("machine_type", &["machine_type"]),
+    ("init_system", &["init_system"]),
+    ("boot_time", &["boot_time"]),
+    ("cpu", &["cpu"]),
+    ("cpu_cores", &["cpu"]),
+    ("cpu_threads", &["cpu"]),
+    ("cpu_freq", &["cpu"]),
+    ("cpu_freq_mhz", &["cpu"]),
+    ("cpu_arch", &["cpu_arch"]),
+    ("cpu_cache", &["cpu_cache"]),
+    ("cpu_temp", &["cpu_temp"]),
+    ("cpu_temp_celsius", &["cpu_temp"]),
+    ("cpu_governor", &["cpu_governor"]),
+    ("gpu", &["gpu"]),
+    ("memory", &["memory"]),
+    ("memory_used", &["memory"]),
+    ("memory_total", &["memory"]),
+    ("swap", &["swap"]),
+    ("swap_used", &["swap"]),
+    ("swap_total", &["swap"]),
+    ("disks", &["disk"]),
+    ("motherboard", &["motherboard"]),
+    ("bios", &["bios"]),
+    ("de", &["de"]),
+    ("wm", &["wm"]),
+    ("wm_theme", &["wm", "wm_theme"]),
+    ("theme", &["theme"]),
+    ("icons", &["icons"]),
+    ("cursor", &["cursor"]),
+    ("terminal", &["terminal"]),
+    ("terminal_font", &["terminal", "terminal_font"]),
+    ("shell", &["shell"]),
+    ("shell_version", &["shell", "shell_version"]),
+    ("display_server", &["display_server"]),
+    ("resolution", &["resolution"]),
+    ("interfaces", &["network"]),
+    ("public_ip", &["public_ip"]),
+    ("battery", &["battery"]),
+    ("brightness", &["brightness"]),
+    ("brightness_percent", &["brightness"]),
+    ("audio_device", &["audio_device"]),
+    ("volume", &["volume"]),
+    ("volume_percent", &["volume"]),
+    ("volume_muted", &["volume"]),
+    ("packages", &["packages"]),
+    ("package_counts", &["packages"]),
+    ("locale", &["locale"]),
+    ("timezone", &["timezone"]),
+    ("virtualization", &["virtualization"]),
+    ("container", &["container"]),
+    ("security", &["security"]),
+    ("ssh_connection", &["ssh_connection"]),
+    ("bluetooth", &["bluetooth"]),
+];
+
+impl Query {
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut chars = path.trim().chars().peekable();
+        let mut key = String::new();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '.' => {
+                    if key.is_empty() && !matches!(segments.last(), Some(Segment::Select(_))) {
+                        return Err(format!("empty field name in '{}'", path));
+                    }
+                    push_key(&mut segments, &mut key);
+                }
+                '[' => {
+                    push_key(&mut segments, &mut key);
+                    if segments.is_empty() {
+                        return Err(format!("'{}' must start with a field name", path));
+                    }
+                    let mut selector = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(c) => selector.push(c),
+                            None => return Err(format!("missing ']' in '{}'", path)),
+                        }
+                    }
+                    if selector.is_empty() {
+                        return Err(format!("empty selector in '{}'", path));
+                    }
+                    segments.push(Segment::Select(selector));
+                    if chars.peek().is_some_and(|c| *c != '.' && *c != '[') {
+                        return Err(format!("expected '.' or '[' after a selector in '{}'", path));
+                    }
+                }
+                c if c.is_alphanumeric() || c == '_' || c == '-' => key.push(c),
+                c => return Err(format!("unexpected '{}' in '{}'", c, path)),
+            }
+        }
+        push_key(&mut segments, &mut key);
+
+        if segments.is_empty() {
+            return Err("empty path".to_string());
+        }
+        if path.trim().ends_with('.') {
+            return Err(format!("'{}' ends with '.'", path));
+        }
+        Ok(Self {
+            path: path.trim().to_string(),
+            segments,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The `[info]` toggles needed for this path. Paths that name no known
+    /// field gather everything, so the lookup can still try all of them.
+    pub fn options(&self) -> Result<InfoOptions, String> {
+        let keys: Vec<&str> = self
+            .segments
+            .iter()
+            .map_while(|segment| match segment {
+                Segment::Key(key) => Some(key.as_str()),
+                Segment::Select(_) => None,
+            })
+            .collect();
+
+        // Longest underscore-joined prefix that names a field, like the lookup
+        for len in (1..=keys.len()).rev() {
+            let field = keys[..len].join("_");
+            if let Some((_, toggles)) = TOGGLES.iter().find(|(name, _)| *name == field) {
+                return InfoOptions::only(toggles);
+            }
+        }
+        Ok(InfoOptions::default())
+    }
+
+    /// The value at this path as printed by `--get`: strings and numbers as
+    /// they are, lists and objects as JSON.
+    pub fn get(&self, info: &SystemInfo) -> Option<String> {
+        let root = serde_json::to_value(info).ok()?;
+        let derived = Value::Object(derived_fields(info));
+        let value = resolve(&root, &self.segments).or_else(|| resolve(&derived, &self.segments))?;
+
+        match value {
+            Value::Null => None,
+            Value::String(s) => Some(s.clone()),
+            Value::Number(_) | Value::Bool(_) => Some(value.to_string()),
+            Value::Array(_) | Value::Object(_) => serde_json::to_string_pretty(value).ok(),
+        }
+    }
+}
+
+fn push_key(segments: &mut Vec<Segment>, key: &mut String) {
+    if !key.is_empty() {
+        segments.push(Segment::Key(std::mem::take(key)));
+    }
+}
+
+fn resolve<'a>(value: &'a Value, segments: &[Segment]) -> Option<&'a Value> {
+    let Some(first) = segments.first() else {
+        return Some(value);
+    };
+
+    match (value, first) {
+        (Value::Object(map), Segment::Key(_)) => {
+            // "memory.used" also matches "memory_used", try the longest join first
+            let keys = segments
+                .iter()
+                .take_while(|segment| matches!(segment, Segment::Key(_)))
+                .count();
+            (1..=keys).rev().find_map(|len| {
+                let name: Vec<&str> = segments[..len]
+                    .iter()
+                    .filter_map(|segment| match segment {
+                        Segment::Key(key) => Some(key.as_str()),
+                        Segment::Select(_) => None,
+                    })
+                    .collect();
+                map.get(&name.join("_"))
+                    .and_then(|child| resolve(child, &segments[len..]))
+            })
+        }
+        (Value::Array(items), Segment::Select(selector)) => {
+            let item = match selector.parse::<usize>() {
+                Ok(index) => items.get(index),
+                Err(_) => items.iter().find(|item| {
+                    ELEMENT_KEYS
+                        .iter()
+                        .any(|key| item.get(key).and_then(Value::as_str) == Some(selector.as_str()))
+                }),
+            }?;
+            resolve(item, &segments[1..])
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::format;
+    use crate::info::{BatteryInfo, DiskInfo, GpuInfo, NetworkInterface, PackageCount, PublicIpInfo};
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn text(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    // Every field spelled out, so a new one does not compile until it is added
+    // here and, through `every_field_has_toggles`, to TOGGLES
+    fn populated() -> SystemInfo {
+        SystemInfo {
+            os: text("Debian GNU/Linux 12 (bookworm)"),
+            os_id: text("debian"),
+            kernel: text("6.1.0-18-amd64"),
+            hostname: text("box"),
+            uptime_seconds: Some(93_784),
+            load_average: Some([0.5, 0.4, 0.3]),
+            processes: Some(312),
+            logged_users: text("1 (alice)"),
+            machine_type: text("Laptop"),
+            init_system: text("systemd"),
+            boot_time: text("2024-02-01 09:00"),
+            cpu: text("Intel i7-8550U"),
+            cpu_arch: text("x86_64"),
+            cpu_cores: Some(4),
+            cpu_threads: Some(8),
+            cpu_freq_mhz: Some(1800.0),
+            cpu_cache: text("8 MiB"),
+            cpu_temp_celsius: Some(45.0),
+            cpu_governor: text("powersave"),
+            gpu: vec![GpuInfo {
+                name: "Intel UHD Graphics 620".to_string(),
+                driver: text("i915"),
+                vram_bytes: None,
+                temp_celsius: None,
+            }],
+            memory_used: Some(4 * GIB),
+            memory_total: Some(16 * GIB),
+            swap_used: Some(0),
+            swap_total: Some(2 * GIB),
+            disks: vec![disk("/", 42)],
+            motherboard: text("LENOVO 20KH"),
+            bios: text("LENOVO N23ET (01/01/2023)"),
+            de: text("GNOME 45"),
+            wm: text("Mutter"),
+            wm_theme: text("Adwaita"),
+            theme: text("Adwaita"),
+            icons: text("Adwaita"),
+            cursor: text("Adwaita"),
+            terminal: text("kitty"),
+            terminal_font: text("JetBrains Mono 11"),
+            shell: text("bash"),
+            shell_version: text("5.2.15"),
+            display_server: text("Wayland"),
+            resolution: text("1920x1080"),
+            interfaces: vec![NetworkInterface {
+                name: "wlan0".to_string(),
+                ipv4: text("192.168.1.2"),
+                ipv6: None,
+                mac: None,
+                speed: None,
+                state: text("up"),
+            }],
+            public_ip: Some(PublicIpInfo {
+                ip: "203.0.113.7".to_string(),
+                country: None,
+                region: None,
+                city: None,
+                zip: None,
+                isp: None,
+            }),
+            battery: Some(BatteryInfo {
+                percent: 80,
+                status: "Discharging".to_string(),
+                time_remaining_minutes: None,
+            }),
+            brightness_percent: Some(60),
+            audio_device: text("Built-in Audio"),
+            volume_percent: Some(40),
+            volume_muted: Some(false),
+            packages: text("1200 (apt)"),
+            package_counts: vec![PackageCount {
+                manager: "apt".to_string(),
+                count: 1200,
+            }],
+            locale: text("en_US.UTF-8"),
+            timezone: text("Europe/Berlin"),
+            virtualization: text("VT-x"),
+            container: text("none"),
+            security: text("AppArmor"),
+            ssh_connection: text("no"),
+            bluetooth: text("on"),
+        }
+    }
+
+    fn get(path: &str, info: &SystemInfo) -> Option<String> {
+        Query::parse(path).unwrap().get(info)
+    }
+
+    fn disk(mount: &str, percent: u8) -> DiskInfo {
+        DiskInfo {
+            mount: mount.to_string(),
+            filesystem: "ext4".to_string(),
+            size_bytes: 100 * GIB,
+            used_bytes: percent as u64 * GIB,
+            available_bytes: (100 - percent) as u64 * GIB,
+            percent,
+            disk_type: None,
+        }
+    }
+
+    #[test]
+    fn malformed_paths_are_errors() {
+        let error = |path: &str| Query::parse(path).unwrap_err();
+        assert_eq!(error("memory..used"), "empty field name in 'memory..used'");
+        assert_eq!(error(".kernel"), "empty field name in '.kernel'");
+        assert_eq!(error("gpu[0"), "missing ']' in 'gpu[0'");
+        assert_eq!(error("gpu[]"), "empty selector in 'gpu[]'");
+        assert_eq!(error("[0]"), "'[0]' must start with a field name");
+        assert_eq!(error("gpu[0]name"), "expected '.' or '[' after a selector in 'gpu[0]name'");
+        assert_eq!(error("kernel."), "'kernel.' ends with '.'");
+        assert_eq!(error("gpu[0]."), "'gpu[0].' ends with '.'");
+        assert_eq!(error("cpu temp"), "unexpected ' ' in 'cpu temp'");
+        assert_eq!(error("  "), "empty path");
+    }
+
+    #[test]
+    fn dotted_names_match_underscore_fields() {
+        let info = SystemInfo {
+            memory_used: Some(4 * GIB),
+            memory_total: Some(16 * GIB),
+            ..Default::default()
+        };
+        assert_eq!(get("memory.used", &info), Some((4 * GIB).to_string()));
+        assert_eq!(get("memory_used", &info), Some((4 * GIB).to_string()));
+        assert_eq!(get("memory.free", &info), None);
+    }
+
+    #[test]
+    fn selectors_pick_list_elements() {
+        let info = SystemInfo {
+            gpu: vec![GpuInfo {
+                name: "Intel UHD Graphics 620".to_string(),
+                driver: Some("i915".to_string()),
+                vram_bytes: None,
+                temp_celsius: None,
+            }],
+            disks: vec![disk("/", 42), disk("/home", 87)],
+            ..Default::default()
+        };
+        assert_eq!(get("disks[/].percent", &info).as_deref(), Some("42"));
+        assert_eq!(get("disks[/home].percent", &info).as_deref(), Some("87"));
+        assert_eq!(get("disks[1].mount", &info).as_deref(), Some("/home"));
+        assert_eq!(get("disks[/boot].percent", &info), None);
+        assert_eq!(get("gpu[0].driver", &info).as_deref(), Some("i915"));
+        assert_eq!(get("gpu[1]", &info), None);
+
+        let gpu = get("gpu[0]", &info).unwrap();
+        assert!(gpu.starts_with('{'), "{}", gpu);
+        assert!(gpu.contains("\"name\": \"Intel UHD Graphics 620\""), "{}", gpu);
+    }
+
+    #[test]
+    fn display_text_is_the_fallback() {
+        let info = SystemInfo {
+            uptime_seconds: Some(93_784),
+            memory_used: Some(4 * GIB),
+            memory_total: Some(16 * GIB),
+            ..Default::default()
+        };
+        assert_eq!(get("uptime", &info), Some(format::uptime(93_784)));
+        assert_eq!(get("uptime_seconds", &info).as_deref(), Some("93784"));
+        assert_eq!(get("memory", &info), Some(format::usage(4 * GIB, 16 * GIB)));
+        assert_eq!(get("memory", &SystemInfo::default()), None);
+    }
+
+    #[test]
+    fn options_gather_only_what_the_path_needs() {
+        let options = |path: &str| Query::parse(path).unwrap().options().unwrap();
+
+        let memory = options("memory.used");
+        assert!(memory.memory);
+        assert!(!memory.cpu && !memory.disk && !memory.os);
+
+        let disks = options("disks[/].percent");
+        assert!(disks.disk);
+        assert!(!disks.memory);
+
+        let wm_theme = options("wm.theme");
+        assert!(wm_theme.wm && wm_theme.wm_theme);
+        assert!(!wm_theme.theme);
+
+        // Nothing to toggle, the logo reads it anyway
+        let os_id = options("os_id");
+        assert!(!os_id.os && !os_id.kernel);
+
+        // Unknown fields gather everything
+        let unknown = options("no_such_field");
+        assert!(unknown.os && unknown.memory && unknown.cpu);
+    }
+
+    #[test]
+    fn every_field_has_toggles() {
+        let value = serde_json::to_value(populated()).unwrap();
+        let fields = value.as_object().unwrap();
+        for (field, value) in fields {
+            assert!(!value.is_null(), "{} is not populated", field);
+            assert!(TOGGLES.iter().any(|(name, _)| name == field), "{} has no TOGGLES entry", field);
+        }
+        for (name, toggles) in TOGGLES {
+            assert!(InfoOptions::only(toggles).is_ok(), "{}", name);
+        }
+        assert!(InfoOptions::only(&["no_such_toggle"]).is_err());
+    }
+}
@@ -0,0 +1,378 @@
+// JSON Schema of the machine-readable output, for `hyperfetch schema`.
+//
+// The schema is read off the `Deserialize` impls of the output types. A
+// tracing deserializer hands every field a sample value and records which
+// type it asked for, so the schema cannot drift from the structs. A field is
+// optional when the struct still deserializes without it, which is what
+// `Option` fields and `#[serde(default)]` allow; everything else is required.
+//
+// `SCHEMA_VERSION` is part of every output. It is bumped whenever a field is
+// removed, renamed, changes its type or meaning, or becomes optional. Adding
+// fields does not bump it. tests/golden/schema.json holds the current
+// schema, see the README for the policy.
+
+use crate::info::profile::Report;
+use crate::info::SystemInfo;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserialize};
+use serde_json::{json, Map, Value};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fmt;
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// The schema of `--json` output, which also describes the YAML and TOML
+/// output (TOML leaves out null fields). Fails when an output type uses a
+/// serde shape the tracer does not understand.
+pub fn schema() -> Result<Value, String> {
+    let mut schema = trace::<SystemInfo>().map_err(|e| e.to_string())?;
+    let diagnostics = trace::<Report>().map_err(|e| e.to_string())?;
+
+    if let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) {
+        properties.insert(
+            "schema_version".to_string(),
+            json!({ "type": "integer", "const": SCHEMA_VERSION }),
+        );
+        // Only with --profile
+        properties.insert("diagnostics".to_string(), diagnostics);
+    }
+    if let Some(required) = schema.get_mut("required").and_then(Value::as_array_mut) {
+        required.push(json!("schema_version"));
+        required.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+    }
+
+    let mut document = Map::new();
+    document.insert("$schema".to_string(), json!(DIALECT));
+    document.insert("title".to_string(), json!("hyperfetch output"));
+    if let Value::Object(schema) = schema {
+        document.extend(schema);
+    }
+    Ok(Value::Object(document))
+}
+
+// A field is identified by its struct and its name, the same struct can
+// appear at several places
+type Field = (&'static str, &'static str);
+
+#[derive(Default)]
+struct Context {
+    // Field left out of this pass, to see whether the struct needs it
+    skip: Option<Field>,
+    optional: BTreeSet<Field>,
+    seen: RefCell<Vec<Field>>,
+}
+
+fn trace<T: for<'de> Deserialize<'de>>() -> Result<Value, Error> {
+    // First pass finds the fields, then one pass without each of them
+    let mut context = Context::default();
+    let mut schema = Value::Null;
+    T::deserialize(Tracer { context: &context, schema: &mut schema })?;
+
+    let fields = context.seen.take();
+    let mut optional = BTreeSet::new();
+    for field in fields {
+        let probe = Context {
+            skip: Some(field),
+            ..Default::default()
+        };
+        if T::deserialize(Tracer { context: &probe, schema: &mut Value::Null }).is_ok() {
+            optional.insert(field);
+        }
+    }
+
+    context.optional = optional;
+    T::deserialize(Tracer { context: &context, schema: &mut schema })?;
+    Ok(schema)
+}
+
+#[derive(Debug)]
+struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+struct Tracer<'a> {
+    context: &'a Context,
+    schema: &'a mut Value,
+}
+
+impl Tracer<'_> {
+    fn nested<'a>(context: &'a Context, schema: &'a mut Value) -> Tracer<'a> {
+        Tracer { context, schema }
+    }
+}
+
+macro_rules! trace_number {
+    ($($method:ident => $visit:ident($sample:expr), $schema:tt;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                *self.schema = json!($schema);
+                visitor.$visit($sample)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Tracer<'_> {
+    type Error = Error;
+
+    // Only types that say what they expect can be traced
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(de::Error::custom("self-describing types cannot be traced"))
+    }
+
+    trace_number! {
+        deserialize_i8 => visit_i8(0), { "type": "integer" };
+        deserialize_i16 => visit_i16(0), { "type": "integer" };
+        deserialize_i32 => visit_i32(0), { "type": "integer" };
+        deserialize_i64 => visit_i64(0), { "type": "integer" };
+        deserialize_u8 => visit_u8(0), { "type": "integer", "minimum": 0 };
+        deserialize_u16 => visit_u16(0), { "type": "integer", "minimum": 0 };
+        deserialize_u32 => visit_u32(0), { "type": "integer", "minimum": 0 };
+        deserialize_u64 => visit_u64(0), { "type": "integer", "minimum": 0 };
+        deserialize_f32 => visit_f32(0.0), { "type": "number" };
+        deserialize_f64 => visit_f64(0.0), { "type": "number" };
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        *self.schema = json!({ "type": "boolean" });
+        visitor.visit_bool(false)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        *self.schema = json!({ "type": "string", "minLength": 1, "maxLength": 1 });
+        visitor.visit_char(' ')
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        *self.schema = json!({ "type": "string" });
+        visitor.visit_str("")
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut inner = Value::Null;
+        let value = visitor.visit_some(Tracer::nested(self.context, &mut inner))?;
+        *self.schema = nullable(inner);
+        Ok(value)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        *self.schema = json!({ "type": "null" });
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut items = Vec::new();
+        let value = visitor.visit_seq(Elements {
+            context: self.context,
+            remaining: 1,
+            items: &mut items,
+        })?;
+        *self.schema = json!({ "type": "array", "items": items.pop().unwrap_or_default() });
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        let mut items = Vec::new();
+        let value = visitor.visit_seq(Elements {
+            context: self.context,
+            remaining: len,
+            items: &mut items,
+        })?;
+        // Fixed-size arrays, [f64; 3] and the like
+        *self.schema = if items.windows(2).all(|pair| pair[0] == pair[1]) {
+            json!({
+                "type": "array",
+                "items": items.first().cloned().unwrap_or_default(),
+                "minItems": len,
+                "maxItems": len,
+            })
+        } else {
+            json!({ "type": "array", "prefixItems": items, "items": false })
+        };
+        Ok(value)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut values = Value::Null;
+        let value = visitor.visit_map(Entry {
+            context: self.context,
+            done: false,
+            value: &mut values,
+        })?;
+        *self.schema = json!({ "type": "object", "additionalProperties": values });
+        Ok(value)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let mut properties = Map::new();
+        let value = visitor.visit_map(Fields {
+            context: self.context,
+            name,
+            fields,
+            next: 0,
+            properties: &mut properties,
+        })?;
+
+        // Sorted like the properties
+        let mut required: Vec<&str> = fields
+            .iter()
+            .copied()
+            .filter(|field| !self.context.optional.contains(&(name, *field)))
+            .collect();
+        required.sort_unstable();
+        *self.schema = json!({ "type": "object", "properties": properties, "required": required });
+        Ok(value)
+    }
+
+    // Unit variants only, which serialize as their name
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        *self.schema = json!({ "type": "string", "enum": variants });
+        let first = variants.first().copied().unwrap_or_default();
+        visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(first))
+    }
+
+    forward_to_deserialize_any! {
+        bytes byte_buf identifier ignored_any
+    }
+}
+
+// `{"type": "string"}` becomes `{"type": ["string", "null"]}`, anything more
+// involved is wrapped in an anyOf
+fn nullable(mut schema: Value) -> Value {
+    match schema.get("type").and_then(Value::as_str).map(str::to_string) {
+        Some(kind) if kind != "null" => {
+            schema["type"] = json!([kind, "null"]);
+            schema
+        }
+        _ => json!({ "anyOf": [schema, { "type": "null" }] }),
+    }
+}
+
+struct Elements<'a> {
+    context: &'a Context,
+    remaining: usize,
+    items: &'a mut Vec<Value>,
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let mut item = Value::Null;
+        let value = seed.deserialize(Tracer::nested(self.context, &mut item))?;
+        self.items.push(item);
+        Ok(Some(value))
+    }
+}
+
+// A single entry with a string key
+struct Entry<'a> {
+    context: &'a Context,
+    done: bool,
+    value: &'a mut Value,
+}
+
+impl<'de> MapAccess<'de> for Entry<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+        seed.deserialize(IntoDeserializer::<Error>::into_deserializer("")).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(Tracer::nested(self.context, self.value))
+    }
+}
+
+struct Fields<'a> {
+    context: &'a Context,
+    name: &'static str,
+    fields: &'static [&'static str],
+    next: usize,
+    properties: &'a mut Map<String, Value>,
+}
+
+impl<'de> MapAccess<'de> for Fields<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        let skipped = self.fields.get(self.next).is_some_and(|field| self.context.skip == Some((self.name, *field)));
+        if skipped {
+            self.next += 1;
+        }
+        match self.fields.get(self.next) {
+            Some(field) => seed.deserialize(IntoDeserializer::<Error>::into_deserializer(*field)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let field = self.fields[self.next];
+        self.next += 1;
+
+        let mut seen = self.context.seen.borrow_mut();
+        if !seen.contains(&(self.name, field)) {
+            seen.push((self.name, field));
+        }
+        drop(seen);
+
+        let mut schema = Value::Null;
+        let value = seed.deserialize(Tracer::nested(self.context, &mut schema))?;
+        self.properties.insert(field.to_string(), schema);
+        Ok(value)
+    }
+}
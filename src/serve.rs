@@ -0,0 +1,186 @@
+// HTTP endpoint for `hyperfetch serve`.
+//
+// Requests are answered from the last gathered `SystemInfo`, which is
+// refreshed on demand once it is older than the minimum interval, so a busy
+// dashboard cannot make hyperfetch gather in a loop. Only GET and HEAD are
+// understood and every response closes the connection.
+
+use crate::display::output::{self, Format};
+use crate::info::SystemInfo;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Requests are a request line and a few headers, anything longer is not ours
+const MAX_REQUEST: usize = 8192;
+// Time a client gets to send its whole request, and for each write
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+// Connections handled at once, more are answered with 503 right away
+const MAX_CONNECTIONS: usize = 64;
+
+const INDEX: &str = "hyperfetch\n\n/json     SystemInfo as JSON\n/metrics  OpenMetrics gauges\n/text     the fetch as plain text\n";
+
+struct Snapshot {
+    min_interval: Duration,
+    last: Mutex<Option<(Instant, Arc<SystemInfo>)>>,
+}
+
+impl Snapshot {
+    // The lock is held while gathering, concurrent requests wait for that
+    // result instead of starting their own
+    fn get(&self, gather: &(dyn Fn() -> SystemInfo + Sync)) -> Arc<SystemInfo> {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        match &*last {
+            Some((at, info)) if at.elapsed() < self.min_interval => info.clone(),
+            _ => {
+                let info = Arc::new(gather());
+                *last = Some((Instant::now(), info.clone()));
+                info
+            }
+        }
+    }
+}
+
+// Gives a connection slot back when its thread ends, also by panicking
+struct Slot<'a>(&'a AtomicUsize);
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Serves until the listener fails. `text` renders the fetch without ANSI codes.
+pub fn run(
+    listen: SocketAddr,
+    min_interval: Duration,
+    gather: &(dyn Fn() -> SystemInfo + Sync),
+    text: &(dyn Fn(&SystemInfo) -> String + Sync),
+) -> io::Result<()> {
+    let listener = TcpListener::bind(listen)?;
+    eprintln!("Listening on http://{}", listener.local_addr()?);
+    serve(listener, min_interval, gather, text);
+    Ok(())
+}
+
+/// Answers requests on `listener` until it fails.
+pub fn serve(
+    listener: TcpListener,
+    min_interval: Duration,
+    gather: &(dyn Fn() -> SystemInfo + Sync),
+    text: &(dyn Fn(&SystemInfo) -> String + Sync),
+) {
+    let snapshot = Snapshot {
+        min_interval,
+        last: Mutex::new(None),
+    };
+
+    let active = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                active.fetch_sub(1, Ordering::SeqCst);
+                let _ = stream.set_write_timeout(Some(CLIENT_TIMEOUT));
+                let _ = respond(&mut stream, "503 Service Unavailable", "text/plain; charset=utf-8", "Too many connections\n", false);
+                continue;
+            }
+            let (snapshot, active) = (&snapshot, &active);
+            scope.spawn(move || {
+                let _slot = Slot(active);
+                let _ = handle(stream, snapshot, gather, text);
+            });
+        }
+    });
+}
+
+fn handle(
+    mut stream: TcpStream,
+    snapshot: &Snapshot,
+    gather: &(dyn Fn() -> SystemInfo + Sync),
+    text: &(dyn Fn(&SystemInfo) -> String + Sync),
+) -> io::Result<()> {
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let Some((method, path)) = read_request(&mut stream)? else {
+        return respond(&mut stream, "400 Bad Request", "text/plain; charset=utf-8", "Bad request\n", false);
+    };
+    let head = method == "HEAD";
+    if method != "GET" && !head {
+        return respond(&mut stream, "405 Method Not Allowed", "text/plain; charset=utf-8", "Only GET and HEAD are supported\n", false);
+    }
+
+    let (content_type, format) = match path.as_str() {
+        "/" => return respond(&mut stream, "200 OK", "text/plain; charset=utf-8", INDEX, head),
+        "/json" => ("application/json", Some(Format::Json)),
+        "/metrics" => ("application/openmetrics-text; version=1.0.0; charset=utf-8", Some(Format::OpenMetrics)),
+        "/text" => ("text/plain; charset=utf-8", None),
+        _ => return respond(&mut stream, "404 Not Found", "text/plain; charset=utf-8", "Not found\n", head),
+    };
+
+    let info = snapshot.get(gather);
+    let body = match format {
+        Some(format) => match output::render(&info, format, None) {
+            Ok(body) => body,
+            Err(e) => {
+                let message = format!("Error serializing output: {}\n", e);
+                return respond(&mut stream, "500 Internal Server Error", "text/plain; charset=utf-8", &message, head);
+            }
+        },
+        None => text(&info),
+    };
+    respond(&mut stream, "200 OK", content_type, &body, head)
+}
+
+// Method and path without the query string, None if this is not HTTP. The
+// whole request has to arrive within CLIENT_TIMEOUT, not each read, so a
+// client trickling bytes cannot hold on to a connection.
+fn read_request(stream: &mut TcpStream) -> io::Result<Option<(String, String)>> {
+    let deadline = Instant::now() + CLIENT_TIMEOUT;
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && !request.windows(2).any(|w| w == b"\n\n") {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "request took too long"));
+        }
+        stream.set_read_timeout(Some(remaining))?;
+        let read = stream.read(&mut buffer)?;
+        if read == 0 || request.len() + read > MAX_REQUEST {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request.lines().next().unwrap_or("").split_whitespace();
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Ok(None);
+    };
+    if !version.starts_with("HTTP/") {
+        return Ok(None);
+    }
+
+    let path = target.split('?').next().unwrap_or(target);
+    Ok(Some((method.to_string(), path.to_string())))
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str, head: bool) -> io::Result<()> {
+    let header = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    stream.write_all(header.as_bytes())?;
+    if !head {
+        stream.write_all(body.as_bytes())?;
+    }
+    stream.flush()
+}
@@ -0,0 +1,71 @@
+// Usage bars and the `bars.mode` layouts.
+
+use colored::Color;
+use hyperfetch::config::{BarMode, BarOptions, Config};
+use hyperfetch::display::bar::{Bar, Scale};
+use hyperfetch::display::modules::{collect_lines, InfoLine};
+use hyperfetch::info::{BatteryInfo, SystemInfo};
+use std::collections::HashMap;
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+fn values(info: &SystemInfo, module: &str, mode: BarMode) -> Vec<String> {
+    let bars = BarOptions {
+        mode,
+        ..Default::default()
+    };
+    collect_lines(info, &[module.to_string()], &HashMap::new(), &bars)
+        .into_iter()
+        .filter_map(|line| match line {
+            InfoLine::Field { value, .. } => Some(value),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn bars_fill_and_color_by_level() {
+    let options = BarOptions::default();
+    let bar = Bar::new(52.0, Scale::Usage, &options);
+    assert_eq!(bar.text, "[■■■■■□□□□□]");
+    assert_eq!(bar.color, Some(Color::Green));
+
+    assert_eq!(Bar::new(70.0, Scale::Usage, &options).color, Some(Color::Yellow));
+    assert_eq!(Bar::new(90.0, Scale::Usage, &options).color, Some(Color::Red));
+    assert_eq!(Bar::new(150.0, Scale::Usage, &options).text, "[■■■■■■■■■■]");
+
+    // A low battery is the bad end
+    assert_eq!(Bar::new(10.0, Scale::Charge, &options).color, Some(Color::Red));
+    assert_eq!(Bar::new(90.0, Scale::Charge, &options).color, Some(Color::Green));
+    assert_eq!(Bar::new(90.0, Scale::Level, &options).color, None);
+}
+
+#[test]
+fn modes_place_the_bar() {
+    let info = SystemInfo {
+        memory_used: Some(8 * GIB),
+        memory_total: Some(16 * GIB),
+        battery: Some(BatteryInfo {
+            percent: 20,
+            status: "Discharging".to_string(),
+            time_remaining_minutes: None,
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(values(&info, "memory", BarMode::Off), ["8.00 GiB / 16.00 GiB (50%)"]);
+    assert_eq!(values(&info, "memory", BarMode::Beside), ["8.00 GiB / 16.00 GiB (50%) [■■■■■□□□□□]"]);
+    assert_eq!(values(&info, "memory", BarMode::Instead), ["[■■■■■□□□□□] 50%"]);
+    assert_eq!(values(&info, "battery", BarMode::Instead), ["[■■□□□□□□□□] 20% (Discharging)"]);
+}
+
+#[test]
+fn bar_settings_are_checked() {
+    let config: Config = toml::from_str("[bars]\nmode = \"beside\"\n").unwrap();
+    assert_eq!(config.bars.mode, BarMode::Beside);
+    assert!(config.warnings().is_empty());
+    assert!(toml::from_str::<Config>("[bars]\nmode = \"under\"\n").is_err());
+
+    let config: Config = toml::from_str("[bars]\nwarning = 90\ncritical = 60\n").unwrap();
+    assert_eq!(config.warnings(), ["bars.warning (90) is above bars.critical (60), bars skip yellow"]);
+}
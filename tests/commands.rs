@@ -0,0 +1,395 @@
+// Parsing of external command output, driven by a mock runner.
+//
+// The runner, sysroot and command limits are process-wide, so every test
+// takes the same lock. The sysroot points at an empty directory (or a small
+// fixture tree) so nothing from the machine running the tests leaks in.
+
+use hyperfetch::config::InfoOptions;
+use hyperfetch::info::command::{self, CommandOutput, CommandRunner, SystemRunner, TimedOut};
+use hyperfetch::info::{audio, desktop, packages, sysroot, SystemInfo};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+// (command line, exit code, stdout)
+type Canned = &'static [(&'static str, i32, &'static str)];
+
+// (percent, muted)
+type Volume = Option<(u32, bool)>;
+
+struct MockRunner {
+    outputs: HashMap<String, (i32, String)>,
+}
+
+impl CommandRunner for MockRunner {
+    fn run(&self, program: &str, args: &[&str], _timeout: Duration) -> Result<Option<CommandOutput>, TimedOut> {
+        let line = std::iter::once(program).chain(args.iter().copied()).collect::<Vec<_>>().join(" ");
+        Ok(self.outputs.get(&line).map(|(code, stdout)| CommandOutput {
+            code: Some(*code),
+            stdout: stdout.clone().into_bytes(),
+        }))
+    }
+}
+
+struct Fixture {
+    root: PathBuf,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Fixture {
+    fn new(name: &str, canned: Canned) -> Self {
+        let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let root = std::env::temp_dir().join(format!("hyperfetch-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        sysroot::set(Some(root.clone()));
+
+        let outputs = canned
+            .iter()
+            .map(|(line, code, stdout)| (line.to_string(), (*code, stdout.to_string())))
+            .collect();
+        command::set_runner(Arc::new(MockRunner { outputs }));
+
+        Self { root, _guard: guard }
+    }
+
+    fn file(&self, path: &str, content: &str) {
+        let path = self.root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        command::reset_runner();
+        sysroot::set(None);
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+fn gather_with(gather: fn(&mut SystemInfo, &InfoOptions)) -> SystemInfo {
+    let mut info = SystemInfo::default();
+    gather(&mut info, &InfoOptions::default());
+    info
+}
+
+const WPCTL_STATUS: &str = "\
+PipeWire 'pipewire-0' [1.0.5, user@host, cookie:1234]
+ └─ Clients:
+        33. WirePlumber                         [1.0.5, user@host, pid:1201]
+
+Audio
+ ├─ Devices:
+ │      42. Built-in Audio                      [alsa]
+ │
+ ├─ Sinks:
+ │      50. HDMI / DisplayPort 3 Output         [vol: 1.00]
+ │  *   51. Built-in Audio Analog Stereo        [vol: 0.40]
+ │
+ ├─ Sources:
+ │  *   52. Built-in Audio Digital Microphone   [vol: 1.00]
+ │
+ └─ Streams:
+
+Video
+";
+
+const PACTL_SINKS: &str = "\
+Sink #0
+\tState: SUSPENDED
+\tName: alsa_output.pci-0000_00_1f.3.hdmi-stereo
+\tDescription: Built-in Audio Digital Stereo (HDMI)
+Sink #1
+\tState: RUNNING
+\tName: alsa_output.pci-0000_00_1f.3.analog-stereo
+\tDescription: Built-in Audio Analog Stereo
+";
+
+#[test]
+fn audio_device() {
+    let cases: &[(&str, Canned, Option<&str>)] = &[
+        (
+            "wpctl",
+            &[("wpctl status", 0, WPCTL_STATUS)],
+            Some("Built-in Audio Analog Stereo (PipeWire)"),
+        ),
+        (
+            "pactl",
+            &[
+                ("pactl get-default-sink", 0, "alsa_output.pci-0000_00_1f.3.analog-stereo\n"),
+                ("pactl list sinks", 0, PACTL_SINKS),
+            ],
+            Some("Built-in Audio Analog Stereo (PulseAudio)"),
+        ),
+        (
+            "pactl without sink list",
+            &[("pactl get-default-sink", 0, "alsa_output.usb-headset\n")],
+            Some("alsa_output.usb-headset (PulseAudio)"),
+        ),
+        (
+            "aplay",
+            &[(
+                "aplay -l",
+                0,
+                "**** List of PLAYBACK Hardware Devices ****\n\
+                 card 0: PCH [HDA Intel PCH], device 0: ALC257 Analog [ALC257 Analog]\n",
+            )],
+            Some("HDA Intel PCH (ALSA)"),
+        ),
+        (
+            "wpctl fails",
+            &[("wpctl status", 1, WPCTL_STATUS)],
+            None,
+        ),
+        ("nothing installed", &[], None),
+    ];
+
+    for (name, canned, expected) in cases {
+        let _fixture = Fixture::new("audio-device", canned);
+        let info = gather_with(audio::gather);
+        assert_eq!(info.audio_device.as_deref(), *expected, "case: {}", name);
+    }
+}
+
+#[test]
+fn volume() {
+    let cases: &[(&str, Canned, Volume)] = &[
+        ("wpctl", &[("wpctl get-volume @DEFAULT_AUDIO_SINK@", 0, "Volume: 0.74\n")], Some((74, false))),
+        ("wpctl rounding", &[("wpctl get-volume @DEFAULT_AUDIO_SINK@", 0, "Volume: 0.59\n")], Some((59, false))),
+        (
+            "wpctl muted",
+            &[("wpctl get-volume @DEFAULT_AUDIO_SINK@", 0, "Volume: 0.50 [MUTED]\n")],
+            Some((50, true)),
+        ),
+        (
+            "pactl",
+            &[
+                (
+                    "pactl get-sink-volume @DEFAULT_SINK@",
+                    0,
+                    "Volume: front-left: 48000 /  73% / -8.11 dB,   front-right: 48000 /  73% / -8.11 dB\n        balance 0.00\n",
+                ),
+                ("pactl get-sink-mute @DEFAULT_SINK@", 0, "Mute: no\n"),
+            ],
+            Some((73, false)),
+        ),
+        (
+            "pactl muted",
+            &[
+                (
+                    "pactl get-sink-volume @DEFAULT_SINK@",
+                    0,
+                    "Volume: front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB\n",
+                ),
+                ("pactl get-sink-mute @DEFAULT_SINK@", 0, "Mute: yes\n"),
+            ],
+            Some((100, true)),
+        ),
+        (
+            "amixer",
+            &[(
+                "amixer get Master",
+                0,
+                "Simple mixer control 'Master',0\n  Capabilities: pvolume pswitch\n  Limits: Playback 0 - 87\n  Mono: Playback 57 [66%] [-22.50dB] [on]\n",
+            )],
+            Some((66, false)),
+        ),
+        (
+            "amixer muted",
+            &[(
+                "amixer get Master",
+                0,
+                "Simple mixer control 'Master',0\n  Front Left: Playback 32768 [50%] [off]\n",
+            )],
+            Some((50, true)),
+        ),
+        ("nothing installed", &[], None),
+    ];
+
+    for (name, canned, expected) in cases {
+        let _fixture = Fixture::new("volume", canned);
+        let info = gather_with(audio::gather);
+        let volume = info.volume_percent.zip(info.volume_muted);
+        assert_eq!(volume, *expected, "case: {}", name);
+    }
+}
+
+const XRANDR: &str = "\
+Screen 0: minimum 320 x 200, current 4480 x 1440, maximum 16384 x 16384
+eDP-1 connected primary 1920x1080+0+0 (normal left inverted right x axis y axis) 344mm x 194mm
+   1920x1080     60.00*+  59.97
+   1280x720      60.00
+HDMI-1 connected 2560x1440+1920+0 (normal left inverted right x axis y axis) 597mm x 336mm
+   2560x1440     59.95*+
+DP-1 disconnected (normal left inverted right x axis y axis)
+";
+
+const WLR_RANDR: &str = "\
+eDP-1 \"Sharp Corporation 0x14D0 (eDP-1)\"
+  Enabled: yes
+  Modes:
+    1920x1200 px, 59.950001 Hz (preferred, current)
+    1920x1080 px, 60.000000 Hz
+";
+
+#[test]
+fn resolution() {
+    let cases: &[(&str, Canned, Option<&str>)] = &[
+        ("xrandr", &[("xrandr --current", 0, XRANDR)], Some("1920x1080, 2560x1440")),
+        (
+            "xrandr mirrored",
+            &[(
+                "xrandr --current",
+                0,
+                "eDP-1 connected primary 1920x1080+0+0 (normal) 344mm x 194mm\nHDMI-1 connected 1920x1080+0+0 (normal) 527mm x 296mm\n",
+            )],
+            Some("1920x1080"),
+        ),
+        ("wlr-randr", &[("wlr-randr", 0, WLR_RANDR)], Some("1920x1200")),
+        (
+            "xrandr without outputs",
+            &[("xrandr --current", 0, "Can't open display\n"), ("wlr-randr", 0, WLR_RANDR)],
+            Some("1920x1200"),
+        ),
+        ("nothing installed", &[], None),
+    ];
+
+    for (name, canned, expected) in cases {
+        let _fixture = Fixture::new("resolution", canned);
+        let info = gather_with(desktop::gather);
+        assert_eq!(info.resolution.as_deref(), *expected, "case: {}", name);
+    }
+}
+
+#[test]
+fn resolution_framebuffer_fallback() {
+    let fixture = Fixture::new("resolution-fb", &[]);
+    fixture.file("sys/class/graphics/fb0/virtual_size", "1024,768\n");
+
+    let info = gather_with(desktop::gather);
+    assert_eq!(info.resolution.as_deref(), Some("1024x768"));
+}
+
+#[test]
+fn package_counters() {
+    let cases: &[(&str, Canned, Option<&str>)] = &[
+        ("pip", &[("pip list --format=freeze", 0, "requests==2.31.0\nurllib3==2.0.7\n")], Some("2 (2 (pip))")),
+        (
+            "npm",
+            &[("npm list -g --depth=0", 0, "/usr/lib\n├── corepack@0.28.0\n└── npm@10.8.1\n")],
+            Some("2 (2 (npm))"),
+        ),
+        (
+            "snap",
+            &[(
+                "snap list",
+                0,
+                "Name    Version  Rev    Tracking       Publisher   Notes\ncore22  20240111 1122   latest/stable  canonical✓  base\nsnapd   2.61.2   21184  latest/stable  canonical✓  snapd\n",
+            )],
+            Some("2 (2 (snap))"),
+        ),
+        ("snap header only", &[("snap list", 0, "No snaps are installed yet.\n")], None),
+        ("flatpak", &[("flatpak list --app", 0, "Firefox\torg.mozilla.firefox\n")], Some("1 (1 (flatpak))")),
+        ("flatpak empty", &[("flatpak list --app", 0, "")], None),
+        ("rpm", &[("rpm -qa --last", 0, "bash-5.2.26\ncoreutils-9.4\nglibc-2.39\n")], Some("3 (3 (rpm))")),
+        ("xbps", &[("xbps-query -l", 0, "ii base-system-0.114_2 Void Linux base system\n")], Some("1 (1 (xbps))")),
+        ("apk", &[("apk info", 0, "musl\nbusybox\nalpine-baselayout\n")], Some("3 (3 (apk))")),
+        ("failed command", &[("pip list --format=freeze", 1, "a==1\n")], None),
+        (
+            "several managers in table order",
+            &[
+                ("pip list --format=freeze", 0, "a==1\nb==2\nc==3\n"),
+                ("flatpak list --app", 0, "a\nb\n"),
+                ("gem list --no-details", 0, "rake (13.0.6)\n"),
+            ],
+            Some("6 (2 (flatpak), 3 (pip), 1 (gem))"),
+        ),
+        ("nothing installed", &[], None),
+    ];
+
+    for (name, canned, expected) in cases {
+        let _fixture = Fixture::new("packages", canned);
+        let info = gather_with(packages::gather);
+        assert_eq!(info.packages.as_deref(), *expected, "case: {}", name);
+    }
+}
+
+#[test]
+fn package_databases() {
+    let fixture = Fixture::new("package-db", &[]);
+    for entry in ["ALPM_DB_VERSION", "bash-5.2.026-2", "glibc-2.39-1"] {
+        fixture.file(&format!("var/lib/pacman/local/{}", entry), "");
+    }
+    fixture.file(
+        "var/lib/dpkg/status",
+        "Package: bash\nStatus: install ok installed\n\n\
+         Package: vim\nStatus: deinstall ok config-files\n\n\
+         Package: coreutils\nStatus: hold ok installed\n",
+    );
+
+    let info = gather_with(packages::gather);
+    assert_eq!(info.packages.as_deref(), Some("4 (2 (pacman), 2 (apt))"));
+}
+
+#[test]
+fn paths_below_the_root_are_mapped_too() {
+    // Like `--sysroot /var` reading /var/lib/dpkg/status
+    let fixture = Fixture::new("prefix", &[]);
+    let logical = fixture.root.join("status");
+    fixture.file("status", "host");
+    fixture.file(&logical.strip_prefix("/").unwrap().to_string_lossy(), "target");
+
+    assert_eq!(sysroot::read_to_string(&logical).unwrap(), "target");
+}
+
+// Real processes, with the live system as the root
+fn system_runner() -> MutexGuard<'static, ()> {
+    let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    sysroot::set(None);
+    command::reset_runner();
+    guard
+}
+
+#[test]
+fn slow_commands_are_killed() {
+    let _guard = system_runner();
+    let timeout = Duration::from_millis(100);
+
+    let output = SystemRunner.run("echo", &["x"], timeout).unwrap().unwrap();
+    assert_eq!(output.stdout, b"x\n");
+
+    let start = Instant::now();
+    assert_eq!(SystemRunner.run("sleep", &["5"], timeout).unwrap_err(), TimedOut);
+    assert!(start.elapsed() < Duration::from_secs(2), "{:?}", start.elapsed());
+
+    // The shell exits at once, the background sleep keeps stdout open
+    let start = Instant::now();
+    assert_eq!(SystemRunner.run("sh", &["-c", "sleep 5 & echo x"], timeout).unwrap_err(), TimedOut);
+    assert!(start.elapsed() < Duration::from_secs(2), "{:?}", start.elapsed());
+}
+
+#[test]
+fn spent_budget_skips_commands() {
+    let _guard = system_runner();
+    command::configure(Duration::from_secs(2), Duration::from_millis(100));
+    command::start_budget();
+
+    let overruns = command::overruns();
+    let start = Instant::now();
+    assert!(command::run("sleep", &["5"]).is_none());
+    assert!(start.elapsed() < Duration::from_secs(2), "{:?}", start.elapsed());
+    assert!(command::run("echo", &["x"]).is_none());
+    assert_eq!(command::overruns(), overruns + 2);
+
+    // Back to the defaults, with no budget clock running
+    command::configure(Duration::from_secs(2), Duration::ZERO);
+    command::start_budget();
+    command::configure(Duration::from_secs(2), Duration::from_secs(5));
+    assert!(command::run("echo", &["x"]).is_some());
+}
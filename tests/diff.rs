@@ -0,0 +1,75 @@
+// Comparison of snapshots for `hyperfetch diff`.
+
+use hyperfetch::diff::{diff, ChangeKind};
+use hyperfetch::info::{BatteryInfo, DiskInfo, SystemInfo};
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+fn disk(mount: &str, filesystem: &str, percent: u8) -> DiskInfo {
+    DiskInfo {
+        mount: mount.to_string(),
+        filesystem: filesystem.to_string(),
+        size_bytes: 100 * GIB,
+        used_bytes: percent as u64 * GIB,
+        available_bytes: (100 - percent) as u64 * GIB,
+        percent,
+        disk_type: None,
+    }
+}
+
+fn paths(old: &SystemInfo, new: &SystemInfo, all: bool) -> Vec<String> {
+    diff(old, new, all).into_iter().map(|change| change.path).collect()
+}
+
+#[test]
+fn volatile_readings_are_skipped_by_path() {
+    let old = SystemInfo {
+        kernel: Some("6.1.0".to_string()),
+        uptime_seconds: Some(60),
+        disks: vec![disk("/", "ext4", 40)],
+        battery: Some(BatteryInfo {
+            percent: 80,
+            status: "Discharging".to_string(),
+            time_remaining_minutes: None,
+        }),
+        ..Default::default()
+    };
+    let new = SystemInfo {
+        kernel: Some("6.6.0".to_string()),
+        uptime_seconds: Some(120),
+        disks: vec![disk("/", "btrfs", 45)],
+        battery: Some(BatteryInfo {
+            percent: 70,
+            status: "Discharging".to_string(),
+            time_remaining_minutes: None,
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(paths(&old, &new, false), ["disks[/].filesystem", "kernel"]);
+    let all = paths(&old, &new, true);
+    assert!(all.contains(&"disks[/].percent".to_string()), "{:?}", all);
+    assert!(all.contains(&"battery.percent".to_string()), "{:?}", all);
+    assert!(all.contains(&"uptime_seconds".to_string()), "{:?}", all);
+}
+
+#[test]
+fn added_elements_leave_out_volatile_fields() {
+    let old = SystemInfo {
+        disks: vec![disk("/", "ext4", 40)],
+        ..Default::default()
+    };
+    let new = SystemInfo {
+        disks: vec![disk("/", "ext4", 40), disk("/home", "xfs", 10)],
+        ..Default::default()
+    };
+
+    let changes = diff(&old, &new, false);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, "disks[/home]");
+    assert_eq!(changes[0].kind, ChangeKind::Added);
+    let added = changes[0].new.as_ref().unwrap();
+    assert_eq!(added["filesystem"], "xfs");
+    assert!(added.get("percent").is_none());
+    assert!(added.get("size_bytes").is_some());
+}
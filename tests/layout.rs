@@ -0,0 +1,145 @@
+// Display widths and fitting the output to the terminal.
+
+use hyperfetch::ascii::AsciiArt;
+use hyperfetch::config::{Config, InfoAlign};
+use hyperfetch::display::layout::width;
+use colored::Color;
+use hyperfetch::display::{render_info, svg, DisplayConfig, LogoPosition};
+use hyperfetch::info::SystemInfo;
+
+fn info() -> SystemInfo {
+    SystemInfo {
+        os: Some("Debian GNU/Linux 12 (bookworm)".to_string()),
+        kernel: Some("6.1.0-18-amd64 #1 SMP PREEMPT_DYNAMIC Debian 6.1.76-1".to_string()),
+        ..Default::default()
+    }
+}
+
+fn config(columns: usize) -> DisplayConfig {
+    DisplayConfig {
+        use_colors: false,
+        modules: vec!["os".to_string(), "kernel".to_string()],
+        width: Some(columns),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn wide_and_zero_width_characters() {
+    assert_eq!(width("GeForce"), 7);
+    assert_eq!(width("日本語"), 6);
+    assert_eq!(width("💻"), 2);
+    assert_eq!(width("e\u{301}"), 1);
+    assert_eq!(width("नमस्ते"), 4);
+    // Woman, ZWJ, laptop is one glyph
+    assert_eq!(width("👩\u{200d}💻"), 2);
+    assert_eq!(width("a\u{200b}b"), 2);
+    assert_eq!(width("■"), 1);
+}
+
+#[test]
+fn long_lines_are_ellipsized() {
+    let ascii = AsciiArt::for_distro(Some("debian"), false);
+    let columns = ascii.width + 2 + 45;
+    let lines = render_info(&info(), &ascii, &config(columns));
+    assert!(lines[0].ends_with("OS: Debian GNU/Linux 12 (bookworm)"));
+    assert!(lines[1].contains("Kernel: 6.1.0-18-amd64") && lines[1].ends_with('…'));
+    assert!(lines.iter().all(|line| width(line) <= columns));
+}
+
+#[test]
+fn narrow_terminals_stack_the_logo() {
+    let ascii = AsciiArt::for_distro(Some("debian"), false);
+    let lines = render_info(&info(), &ascii, &config(ascii.width));
+    assert_eq!(lines[ascii.lines.len()], "");
+    assert!(lines[ascii.lines.len() + 1].starts_with("OS: Debian"));
+    assert!(lines.iter().all(|line| width(line) <= ascii.width));
+}
+
+#[test]
+fn logo_positions() {
+    let ascii = AsciiArt::for_distro(Some("debian"), true);
+    let place = |position| {
+        let config = DisplayConfig {
+            width: None,
+            logo_position: position,
+            ..config(0)
+        };
+        render_info(&info(), &ascii, &config)
+    };
+
+    let right = place(LogoPosition::Right);
+    assert!(right[0].starts_with("OS: Debian"));
+    assert!(right[0].ends_with(ascii.lines[0].trim_end()));
+
+    let top = place(LogoPosition::Top);
+    assert_eq!(top[ascii.lines.len()], "");
+    assert!(top[ascii.lines.len() + 1].starts_with("OS: "));
+
+    let bottom = place(LogoPosition::Bottom);
+    assert!(bottom[0].starts_with("OS: "));
+    assert_eq!(bottom[2], "");
+}
+
+#[test]
+fn info_can_be_centered_and_offset() {
+    let ascii = AsciiArt::for_distro(Some("debian"), false);
+    let config = DisplayConfig {
+        width: None,
+        logo_gap: 4,
+        info_offset: 1,
+        center_info: true,
+        ..config(0)
+    };
+    let lines = render_info(&info(), &ascii, &config);
+    let row = (ascii.lines.len() - 2) / 2 + 1;
+    assert_eq!(lines.len(), ascii.lines.len());
+    assert_eq!(lines[row].find("OS: "), Some(ascii.width + 4));
+    assert!(!lines[row - 1].contains("OS: "));
+}
+
+#[test]
+fn config_names_positions() {
+    let config: Config = toml::from_str("[display]\nlogo_position = \"bottom\"\ninfo_align = \"center\"\n").unwrap();
+    assert_eq!(config.display.logo_position, LogoPosition::Bottom);
+    assert_eq!(config.display.info_align, InfoAlign::Center);
+
+    let defaults = Config::default();
+    assert_eq!(defaults.display.logo_position, LogoPosition::Left);
+    assert_eq!(defaults.display.info_align, InfoAlign::Top);
+
+    assert!(toml::from_str::<Config>("[display]\nlogo_position = \"middle\"\n").is_err());
+    assert!(toml::from_str::<Config>("[display]\ninfo_align = \"bottom\"\n").is_err());
+}
+
+#[test]
+fn svg_grid_counts_wide_characters() {
+    let info = SystemInfo {
+        os: Some("日本語 Linux".to_string()),
+        kernel: Some("6.1.0".to_string()),
+        ..Default::default()
+    };
+    let ascii = AsciiArt {
+        lines: vec!["##", "##"],
+        colors: vec![Color::Blue],
+        width: 2,
+    };
+    let config = DisplayConfig {
+        width: None,
+        logo_position: LogoPosition::Right,
+        ..config(0)
+    };
+
+    // "OS: 日本語 Linux" is 16 columns, then the gap and the logo
+    let columns = 16 + config.logo_gap + 2;
+    let lines = render_info(&info, &ascii, &config);
+    assert_eq!(width(&lines[0]), columns);
+
+    let image = svg::render(&info, &ascii, &config);
+    let grid = format!("width=\"{:.1}\"", columns as f64 * 8.4 + 40.0);
+    assert!(image.starts_with(&format!("<svg xmlns=\"http://www.w3.org/2000/svg\" {}", grid)), "{}", image);
+
+    // The logo lines up on both rows, after the wide value and after the narrow one
+    let logo_x = format!("<tspan x=\"{:.1}\" fill=\"#cccccc\">##</tspan>", 20.0 + 18.0 * 8.4);
+    assert_eq!(image.matches(&logo_x).count(), 2, "{}", image);
+}
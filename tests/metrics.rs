@@ -0,0 +1,66 @@
+// OpenMetrics exposition of `--format metrics` and `/metrics`.
+
+use hyperfetch::display::metrics;
+use hyperfetch::info::{GpuInfo, SystemInfo};
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+fn info() -> SystemInfo {
+    SystemInfo {
+        hostname: Some("box".to_string()),
+        kernel: Some("6.1.0".to_string()),
+        uptime_seconds: Some(3600),
+        memory_used: Some(4 * GIB),
+        memory_total: Some(16 * GIB),
+        cpu_temp_celsius: Some(45.5),
+        gpu: vec![GpuInfo {
+            name: "Quoted \"GPU\" with C:\\path\nand a newline".to_string(),
+            driver: None,
+            vram_bytes: None,
+            temp_celsius: Some(60.0),
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn exposition_is_complete() {
+    let text = metrics::render(&info(), None);
+    assert!(text.ends_with("\n# EOF\n"), "{}", text);
+    assert_eq!(text.matches("# EOF").count(), 1);
+    assert!(text.contains("hyperfetch_info{hostname=\"box\",kernel=\"6.1.0\"} 1\n"), "{}", text);
+    assert!(text.contains(&format!("hyperfetch_memory_total_bytes {}\n", 16 * GIB)), "{}", text);
+    assert!(text.contains("hyperfetch_cpu_temperature_celsius 45.5\n"), "{}", text);
+}
+
+#[test]
+fn units_match_the_name_suffix() {
+    let text = metrics::render(&info(), None);
+    let units: Vec<(&str, &str)> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("# UNIT "))
+        .filter_map(|line| line.split_once(' '))
+        .collect();
+    assert!(units.len() >= 4, "{}", text);
+    for (name, unit) in units {
+        assert!(name.ends_with(&format!("_{}", unit)), "{} {}", name, unit);
+    }
+}
+
+#[test]
+fn labels_are_escaped() {
+    let text = metrics::render(&info(), None);
+    let expected = r#"hyperfetch_gpu_temperature_celsius{index="0",name="Quoted \"GPU\" with C:\\path\nand a newline"} 60"#;
+    assert!(text.contains(&format!("{}\n", expected)), "{}", text);
+}
+
+#[test]
+fn missing_fields_leave_out_their_family() {
+    let text = metrics::render(&info(), None);
+    for family in ["swap_used_bytes", "processes", "load1", "battery_percent", "disk_size_bytes", "packages"] {
+        assert!(!text.contains(&format!("hyperfetch_{}", family)), "{} in {}", family, text);
+    }
+
+    let empty = metrics::render(&SystemInfo::default(), None);
+    assert_eq!(empty, "# EOF\n");
+}
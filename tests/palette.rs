@@ -0,0 +1,38 @@
+// Color values accepted in the config and logo palettes.
+
+use colored::Color;
+use hyperfetch::display::palette::{from_index, parse};
+
+#[test]
+fn parses_every_notation() {
+    let arch_blue = Some(Color::TrueColor { r: 0x17, g: 0x93, b: 0xd1 });
+    assert_eq!(parse("#1793d1"), arch_blue);
+    assert_eq!(parse("#1793D1"), arch_blue);
+    assert_eq!(parse("rgb(23, 147, 209)"), arch_blue);
+    assert_eq!(parse("#fff"), Some(Color::TrueColor { r: 255, g: 255, b: 255 }));
+
+    assert_eq!(parse("cyan"), Some(Color::Cyan));
+    assert_eq!(parse("Bright_Red"), Some(Color::BrightRed));
+    assert_eq!(parse("brightred"), Some(Color::BrightRed));
+    assert_eq!(parse("purple"), Some(Color::Magenta));
+
+    // 0-15 follow the terminal theme, the rest are fixed values
+    assert_eq!(parse("9"), Some(Color::BrightRed));
+    assert_eq!(parse("32"), Some(Color::TrueColor { r: 0, g: 135, b: 215 }));
+    assert_eq!(parse("244"), Some(Color::TrueColor { r: 128, g: 128, b: 128 }));
+}
+
+#[test]
+fn rejects_malformed_values() {
+    for value in ["", "#12345", "#ggg", "rgb(1, 2)", "rgb(1, 2, 300)", "256", "-1", "teal"] {
+        assert_eq!(parse(value), None, "{}", value);
+    }
+}
+
+#[test]
+fn cube_and_grays() {
+    assert_eq!(from_index(16), Color::TrueColor { r: 0, g: 0, b: 0 });
+    assert_eq!(from_index(231), Color::TrueColor { r: 255, g: 255, b: 255 });
+    assert_eq!(from_index(232), Color::TrueColor { r: 8, g: 8, b: 8 });
+    assert_eq!(from_index(255), Color::TrueColor { r: 238, g: 238, b: 238 });
+}
@@ -0,0 +1,114 @@
+// The JSON Schema of the output against tests/golden/schema.json.
+//
+// Any schema change fails `schema_matches_golden`. When the change is
+// intended, regenerate the golden file with
+//
+//   UPDATE_GOLDEN=1 cargo test --test schema
+//
+// and commit it. Changes that can break consumers (a field removed, its type
+// changed, a required field made optional) are refused until SCHEMA_VERSION
+// is bumped, see "JSON compatibility" in the README.
+
+use hyperfetch::display::output::{self, Format};
+use hyperfetch::info::SystemInfo;
+use hyperfetch::schema::{self, SCHEMA_VERSION};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+fn golden_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/schema.json")
+}
+
+// Paths in `old` that `new` no longer satisfies the same way
+fn breaking_changes(old: &Value, new: &Value, path: &str, changes: &mut Vec<String>) {
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        if old != new {
+            changes.push(format!("{}: changed", path));
+        }
+        return;
+    };
+
+    for (key, old_value) in old {
+        let new_value = new.get(key);
+        match key.as_str() {
+            "properties" => {
+                let new_properties = new_value.and_then(Value::as_object);
+                for (name, old_property) in old_value.as_object().into_iter().flatten() {
+                    let field = format!("{}.{}", path, name);
+                    match new_properties.and_then(|p| p.get(name)) {
+                        Some(new_property) => breaking_changes(old_property, new_property, &field, changes),
+                        None => changes.push(format!("{}: removed", field)),
+                    }
+                }
+            }
+            "required" => {
+                let new_required = new_value.and_then(Value::as_array);
+                for name in old_value.as_array().into_iter().flatten() {
+                    if !new_required.is_some_and(|r| r.contains(name)) {
+                        changes.push(format!("{}.{}: no longer required", path, name.as_str().unwrap_or_default()));
+                    }
+                }
+            }
+            "items" | "additionalProperties" => match new_value {
+                Some(new_value) => breaking_changes(old_value, new_value, &format!("{}[]", path), changes),
+                None => changes.push(format!("{}: {} removed", path, key)),
+            },
+            _ if new_value != Some(old_value) => changes.push(format!("{}: {} changed", path, key)),
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn schema_matches_golden() {
+    let current = schema::schema().unwrap();
+    let golden: Value = fs::read_to_string(golden_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    let golden_version = golden["properties"]["schema_version"]["const"].as_u64();
+    if golden_version == Some(u64::from(SCHEMA_VERSION)) {
+        let mut changes = Vec::new();
+        breaking_changes(&golden, &current, "", &mut changes);
+        assert!(
+            changes.is_empty(),
+            "incompatible schema changes need a new SCHEMA_VERSION:\n  {}",
+            changes.join("\n  ")
+        );
+    }
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let json = serde_json::to_string_pretty(&current).unwrap() + "\n";
+        fs::write(golden_path(), json).unwrap();
+        return;
+    }
+    assert!(
+        current == golden,
+        "the schema changed, if that is intended run `UPDATE_GOLDEN=1 cargo test --test schema` and commit tests/golden/schema.json"
+    );
+}
+
+#[test]
+fn output_carries_schema_version() {
+    let json = output::render(&SystemInfo::default(), Format::Json, None).unwrap();
+    let value: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["schema_version"], Value::from(SCHEMA_VERSION));
+}
+
+#[test]
+fn newer_snapshots_are_rejected() {
+    let path = std::env::temp_dir().join(format!("hyperfetch-schema-{}.json", std::process::id()));
+    let snapshot = format!(r#"{{"schema_version": {}, "kernel": "6.1.0"}}"#, SCHEMA_VERSION + 1);
+    fs::write(&path, snapshot).unwrap();
+    let result = SystemInfo::load_from(&path);
+    fs::remove_file(&path).unwrap();
+    assert!(result.is_err());
+
+    let current = format!(r#"{{"schema_version": {}, "kernel": "6.1.0"}}"#, SCHEMA_VERSION);
+    fs::write(&path, current).unwrap();
+    let info = SystemInfo::load_from(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(info.kernel.as_deref(), Some("6.1.0"));
+}
@@ -0,0 +1,105 @@
+// The HTTP endpoint of `hyperfetch serve`, on a random local port.
+
+use hyperfetch::info::SystemInfo;
+use hyperfetch::serve;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+
+static GATHERS: AtomicUsize = AtomicUsize::new(0);
+
+// One server for all tests, so the gather count covers every request
+fn server() -> SocketAddr {
+    static ADDR: OnceLock<SocketAddr> = OnceLock::new();
+    *ADDR.get_or_init(|| {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let gather = || {
+                GATHERS.fetch_add(1, Ordering::SeqCst);
+                SystemInfo {
+                    kernel: Some("6.1.0".to_string()),
+                    memory_used: Some(1024),
+                    ..Default::default()
+                }
+            };
+            let text = |info: &SystemInfo| format!("Kernel: {}\n", info.kernel.as_deref().unwrap_or("?"));
+            serve::serve(listener, Duration::from_secs(3600), &gather, &text);
+        });
+        addr
+    })
+}
+
+struct Response {
+    status: String,
+    content_type: String,
+    body: String,
+}
+
+fn request(method: &str, path: &str) -> Response {
+    let mut stream = TcpStream::connect(server()).unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\nHost: localhost\r\n\r\n", method, path).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let mut lines = head.lines();
+    let status = lines.next().unwrap().trim_start_matches("HTTP/1.1 ").to_string();
+    let content_type = lines
+        .find_map(|line| line.strip_prefix("Content-Type: "))
+        .unwrap_or_default()
+        .to_string();
+    Response {
+        status,
+        content_type,
+        body: body.to_string(),
+    }
+}
+
+#[test]
+fn endpoints() {
+    let index = request("GET", "/");
+    assert_eq!(index.status, "200 OK");
+    assert_eq!(index.content_type, "text/plain; charset=utf-8");
+    assert!(index.body.contains("/metrics"));
+
+    let json = request("GET", "/json?pretty=1");
+    assert_eq!(json.status, "200 OK");
+    assert_eq!(json.content_type, "application/json");
+    let value: serde_json::Value = serde_json::from_str(&json.body).unwrap();
+    assert_eq!(value["kernel"], "6.1.0");
+
+    let metrics = request("GET", "/metrics");
+    assert_eq!(metrics.status, "200 OK");
+    assert_eq!(metrics.content_type, "application/openmetrics-text; version=1.0.0; charset=utf-8");
+    assert!(metrics.body.contains("hyperfetch_memory_used_bytes 1024\n"));
+    assert!(metrics.body.ends_with("# EOF\n"));
+
+    let text = request("GET", "/text");
+    assert_eq!(text.status, "200 OK");
+    assert_eq!(text.content_type, "text/plain; charset=utf-8");
+    assert_eq!(text.body, "Kernel: 6.1.0\n");
+}
+
+#[test]
+fn errors_and_head() {
+    assert_eq!(request("GET", "/nope").status, "404 Not Found");
+    assert_eq!(request("POST", "/json").status, "405 Method Not Allowed");
+
+    let head = request("HEAD", "/json");
+    assert_eq!(head.status, "200 OK");
+    assert_eq!(head.content_type, "application/json");
+    assert_eq!(head.body, "");
+}
+
+#[test]
+fn requests_within_the_interval_share_a_gather() {
+    request("GET", "/json");
+    request("GET", "/text");
+    request("GET", "/metrics");
+    assert_eq!(GATHERS.load(Ordering::SeqCst), 1);
+}
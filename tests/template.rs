@@ -0,0 +1,51 @@
+// Parsing and rendering of the `[templates]` format strings.
+
+use hyperfetch::display::template::Template;
+
+fn render(source: &str, fields: &[(&str, &str)]) -> String {
+    Template::parse(source).render(|name| {
+        fields
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.to_string())
+    })
+}
+
+#[test]
+fn missing_placeholders_render_as_question_marks() {
+    assert_eq!(render("{name} {version}", &[("name", "bash")]), "bash ?");
+    assert_eq!(render("{cpu.cores}", &[("cpu.cores", "8")]), "8");
+}
+
+#[test]
+fn conditionals_nest() {
+    let source = "{name}{? ({driver}{? {vram}})}";
+    assert_eq!(render(source, &[("name", "GPU")]), "GPU");
+    assert_eq!(render(source, &[("name", "GPU"), ("driver", "i915")]), "GPU (i915)");
+    assert_eq!(
+        render(source, &[("name", "GPU"), ("driver", "i915"), ("vram", "2 GiB")]),
+        "GPU (i915 2 GiB)"
+    );
+    // A missing value only drops its own segment, not the one around it
+    assert_eq!(render(source, &[("name", "GPU"), ("vram", "2 GiB")]), "GPU");
+}
+
+#[test]
+fn doubled_braces_are_literal() {
+    assert_eq!(render("{{name}}", &[("name", "bash")]), "{name}");
+    assert_eq!(render("{{{name}}}", &[("name", "bash")]), "{bash}");
+    // Inside a conditional the first `}` closes it, the second is text
+    assert_eq!(render("{?[{name}]}}", &[("name", "bash")]), "[bash]}");
+    assert_eq!(render("{?[{name}]}}", &[]), "}");
+}
+
+#[test]
+fn malformed_templates_stay_text() {
+    assert_eq!(render("{name", &[("name", "bash")]), "{name");
+    assert_eq!(render("{not a field}", &[]), "{not a field}");
+    assert_eq!(render("{}", &[]), "{}");
+    assert_eq!(render("50% }", &[]), "50% }");
+    // An unclosed conditional runs to the end
+    assert_eq!(render("{name}{? ({version})", &[("name", "bash"), ("version", "5.2")]), "bash (5.2)");
+    assert_eq!(render("{name}{? ({version})", &[("name", "bash")]), "bash");
+}
@@ -0,0 +1,59 @@
+// Built-in themes, layering and color checks.
+
+use colored::Color;
+use hyperfetch::config::{StyleOptions, ThemeOptions};
+use hyperfetch::display::theme::{self, Theme};
+
+fn named(name: &str) -> ThemeOptions {
+    ThemeOptions {
+        name: Some(name.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn builtin_themes_load() {
+    for name in theme::names() {
+        assert!(theme::load(named(name)).is_ok(), "theme {}", name);
+    }
+}
+
+#[test]
+fn default_theme_matches_classic_output() {
+    let theme = Theme::default();
+    assert_eq!(theme.label.color, Some(Color::Cyan));
+    assert!(theme.label.bold);
+    assert_eq!(theme.separator.color, Some(Color::Cyan));
+    assert_eq!(theme.value.color, None);
+    assert_eq!(theme.bar_palette.len(), 8);
+}
+
+#[test]
+fn config_fields_override_the_theme() {
+    let options = ThemeOptions {
+        value: StyleOptions {
+            italic: Some(true),
+            ..Default::default()
+        },
+        ..named("duo")
+    };
+    let options = theme::load(options).unwrap();
+    let theme = Theme::new(&options, Color::Blue, Some(Color::Green));
+    assert_eq!(theme.label.color, Some(Color::Blue));
+    assert_eq!(theme.value.color, Some(Color::Green));
+    assert!(theme.value.italic);
+}
+
+#[test]
+fn unknown_themes_and_colors_are_errors() {
+    assert!(theme::load(named("no-such-theme")).is_err());
+
+    let options = ThemeOptions {
+        label: StyleOptions {
+            color: Some("chartreuse-ish".to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(theme::load(options).is_err());
+}